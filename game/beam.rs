@@ -0,0 +1,35 @@
+//beams: weapons that hit instantly along a line instead of firing projectiles
+//a beam stops at the first thing it can hit, and does damage to it for as long as fire is held
+
+use crate::model::ModelId;
+use crate::pool::EntityHandle;
+
+//one beam fired during the last tick. kept so the draw code can show it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Beam {
+    //the entity firing the beam
+    pub shooter: EntityHandle,
+    //the model that is stretched out to draw the beam
+    pub model: ModelId,
+    //where the beam starts
+    pub x: f32,
+    pub y: f32,
+    //the direction the beam points, with a length of 1
+    pub dir_x: f32,
+    pub dir_y: f32,
+    //how far the beam reached. shorter than its range if it hit something
+    pub length: f32,
+    pub width: f32,
+    //what stopped the beam, if anything did
+    pub hit: Option<EntityHandle>,
+}
+
+impl Beam {
+    //where the beam stops
+    pub fn end(&self) -> (f32, f32) {
+        (
+            self.x + self.dir_x * self.length,
+            self.y + self.dir_y * self.length,
+        )
+    }
+}
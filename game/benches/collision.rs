@@ -0,0 +1,72 @@
+//compares the grid broadphase against testing every pair of entities
+//run with `cargo bench -p game --bench collision`
+
+use game::broadphase::{brute_force_pairs, Grid, CELL_SIZE};
+use game::rng::Rng;
+use game::{Playfield, Shape};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+//scatter bullet sized circles and ship sized boxes over the playfield, using a fixed seed
+fn scatter(count: usize) -> Vec<Shape> {
+    let mut rng = Rng::new(12345);
+    (0..count)
+        .map(|i| {
+            let x = rng.range(0.0, 1000.0);
+            let y = rng.range(0.0, 600.0);
+            if i % 10 == 0 {
+                Shape::Aabb {
+                    x,
+                    y,
+                    half_width: 20.0,
+                    half_height: 20.0,
+                }
+            } else {
+                Shape::Circle { x, y, radius: 4.0 }
+            }
+        })
+        .collect()
+}
+
+//average time per run of a function, running it until about half a second has passed
+fn time<F: FnMut()>(mut f: F) -> Duration {
+    let start = Instant::now();
+    let mut runs = 0;
+    while runs < 3 || start.elapsed() < Duration::from_millis(500) {
+        f();
+        runs += 1;
+    }
+    start.elapsed() / runs
+}
+
+fn main() {
+    println!(
+        "{:>8} {:>14} {:>14} {:>8}",
+        "shapes", "every pair", "grid", "speedup"
+    );
+    for count in [1000, 5000, 10000] {
+        let shapes = scatter(count);
+        let mut pairs = Vec::new();
+
+        let brute = time(|| {
+            pairs.clear();
+            brute_force_pairs(black_box(&shapes), &mut pairs);
+            black_box(&pairs);
+        });
+
+        let mut grid = Grid::new(&Playfield::default(), 200.0, CELL_SIZE);
+        let fast = time(|| {
+            pairs.clear();
+            grid.pairs(black_box(&shapes), &mut pairs);
+            black_box(&pairs);
+        });
+
+        println!(
+            "{:>8} {:>14?} {:>14?} {:>7.1}x",
+            count,
+            brute,
+            fast,
+            brute.as_secs_f64() / fast.as_secs_f64()
+        );
+    }
+}
@@ -0,0 +1,136 @@
+//compares storing bullets in the pool against a plain list with Vec::remove
+//10000 bullets a second are fired down the playfield at 60 ticks a second, and removed once they leave it
+//run with `cargo bench -p game --bench pool`
+
+use game::rng::Rng;
+use game::{Entity, Pool};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const BULLETS_PER_SECOND: f32 = 10000.0;
+const DT: f32 = 1.0 / 60.0;
+const BOTTOM: f32 = 600.0;
+
+//a stand in for a projectile, as big as a real entity so moving them around costs the same
+#[derive(Clone)]
+struct Bullet {
+    y: f32,
+    speed: f32,
+    _rest: [u8; std::mem::size_of::<Entity>()],
+}
+
+//what each kind of storage has to do every tick
+trait Store {
+    fn spawn(&mut self, bullet: Bullet);
+    //move every bullet, removing the ones that left the playfield
+    fn step(&mut self);
+    fn count(&self) -> usize;
+}
+
+//how the game used to do it: every removal shifts everything after it down
+impl Store for Vec<Bullet> {
+    fn spawn(&mut self, bullet: Bullet) {
+        self.push(bullet);
+    }
+
+    fn step(&mut self) {
+        let mut i = 0;
+        while i < self.len() {
+            self[i].y += self[i].speed * DT;
+            if self[i].y > BOTTOM {
+                self.remove(i);
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    fn count(&self) -> usize {
+        self.len()
+    }
+}
+
+impl Store for Pool<Bullet> {
+    fn spawn(&mut self, bullet: Bullet) {
+        self.acquire(bullet);
+    }
+
+    fn step(&mut self) {
+        let mut gone = Vec::new();
+        for (handle, b) in self.iter_mut() {
+            b.y += b.speed * DT;
+            if b.y > BOTTOM {
+                gone.push(handle);
+            }
+        }
+        for handle in gone {
+            self.release(handle);
+        }
+    }
+
+    fn count(&self) -> usize {
+        self.len()
+    }
+}
+
+//fires bullets at a steady rate, carrying over the fraction of a bullet left each tick
+struct Gun {
+    rng: Rng,
+    owed: f32,
+}
+
+impl Gun {
+    fn tick<S: Store>(&mut self, store: &mut S) {
+        self.owed += BULLETS_PER_SECOND * DT;
+        while self.owed >= 1.0 {
+            self.owed -= 1.0;
+            store.spawn(Bullet {
+                y: 0.0,
+                speed: self.rng.range(300.0, 600.0),
+                _rest: [0; std::mem::size_of::<Entity>()],
+            });
+        }
+        store.step();
+    }
+}
+
+//average time to simulate one second of play, after the bullet count has settled
+//also returns how many bullets were alive at the end
+fn time<S: Store>(mut store: S) -> (Duration, usize) {
+    let mut gun = Gun {
+        rng: Rng::new(12345),
+        owed: 0.0,
+    };
+    //bullets take up to two seconds to cross the playfield
+    for _ in 0..120 {
+        gun.tick(&mut store);
+    }
+
+    let start = Instant::now();
+    let mut runs = 0;
+    while runs < 3 || start.elapsed() < Duration::from_millis(500) {
+        for _ in 0..60 {
+            gun.tick(black_box(&mut store));
+        }
+        runs += 1;
+    }
+    (start.elapsed() / runs, store.count())
+}
+
+fn main() {
+    let (list, live) = time(Vec::new());
+    let (pool, _) = time(Pool::new());
+    println!(
+        "{} bullets a second, about {} alive at once. time per second of play:",
+        BULLETS_PER_SECOND, live
+    );
+    println!("{:>12} {:>14} {:>8}", "storage", "time", "speedup");
+    for (name, took) in [("Vec::remove", list), ("pool", pool)] {
+        println!(
+            "{:>12} {:>14?} {:>7.1}x",
+            name,
+            took,
+            list.as_secs_f64() / took.as_secs_f64()
+        );
+    }
+}
@@ -0,0 +1,132 @@
+//uniform grid broadphase
+//the playfield is cut into square cells, and every shape is put in each cell its bounding box covers
+//only shapes that share a cell are tested against each other, instead of every shape against every other
+
+use crate::collision::Shape;
+use crate::playfield::Playfield;
+
+//size of each cell, in playfield units. a bit bigger than a ship works well
+pub const CELL_SIZE: f32 = 64.0;
+
+//most cells the grid will have along either side. huge playfields get bigger cells instead of more of them
+const MAX_CELLS_PER_SIDE: f32 = 128.0;
+
+#[derive(Clone, Debug)]
+pub struct Grid {
+    //the playfield the grid was built for
+    playfield: Playfield,
+    //top left corner of the area the grid covers
+    left: f32,
+    top: f32,
+    cell_size: f32,
+    cols: usize,
+    rows: usize,
+    //which shapes are in each cell, by their index in the list given to pairs
+    cells: Vec<Vec<u32>>,
+}
+
+impl Grid {
+    //create a grid covering the playfield plus a margin on every side
+    //anything outside that area is treated as being in the nearest edge cell
+    pub fn new(playfield: &Playfield, margin: f32, cell_size: f32) -> Grid {
+        let width = playfield.width() + margin * 2.0;
+        let height = playfield.height() + margin * 2.0;
+        let cell_size = cell_size
+            .max(width / MAX_CELLS_PER_SIDE)
+            .max(height / MAX_CELLS_PER_SIDE);
+        let cols = ((width / cell_size).ceil() as usize).max(1);
+        let rows = ((height / cell_size).ceil() as usize).max(1);
+        Grid {
+            playfield: *playfield,
+            left: playfield.left - margin,
+            top: playfield.top - margin,
+            cell_size,
+            cols,
+            rows,
+            cells: vec![Vec::new(); cols * rows],
+        }
+    }
+
+    pub fn playfield(&self) -> &Playfield {
+        &self.playfield
+    }
+
+    //which column and row a point falls in
+    fn cell_of(&self, x: f32, y: f32) -> (usize, usize) {
+        let col = ((x - self.left) / self.cell_size).floor();
+        let row = ((y - self.top) / self.cell_size).floor();
+        (
+            col.clamp(0.0, (self.cols - 1) as f32) as usize,
+            row.clamp(0.0, (self.rows - 1) as f32) as usize,
+        )
+    }
+
+    //find every pair of shapes that touch, as indices into the list
+    //each pair is listed once, with the lower index first
+    pub fn pairs(&mut self, shapes: &[Shape], out: &mut Vec<(usize, usize)>) {
+        self.pairs_with(shapes, out, |a, b| shapes[a].touches(&shapes[b]));
+    }
+
+    //find every pair of shapes that are near each other and pass a test, as indices into the list
+    //the shapes only need to cover everything the test looks at. for moving things, that's the whole path
+    pub fn pairs_with<F: FnMut(usize, usize) -> bool>(
+        &mut self,
+        shapes: &[Shape],
+        out: &mut Vec<(usize, usize)>,
+        mut test: F,
+    ) {
+        for cell in &mut self.cells {
+            cell.clear();
+        }
+
+        //bucket each shape into every cell its bounding box covers
+        let boxes: Vec<(f32, f32, f32, f32)> = shapes.iter().map(|s| s.bounding_box()).collect();
+        for (i, &(left, top, right, bottom)) in boxes.iter().enumerate() {
+            let (c0, r0) = self.cell_of(left, top);
+            let (c1, r1) = self.cell_of(right, bottom);
+            for row in r0..=r1 {
+                for col in c0..=c1 {
+                    self.cells[row * self.cols + col].push(i as u32);
+                }
+            }
+        }
+
+        for (index, cell) in self.cells.iter().enumerate() {
+            for (n, &a) in cell.iter().enumerate() {
+                for &b in &cell[n + 1..] {
+                    let (a, b) = (a as usize, b as usize);
+                    let (a_box, b_box) = (boxes[a], boxes[b]);
+                    //cheap check first: shapes whose boxes don't overlap can't touch
+                    if a_box.0 > b_box.2
+                        || b_box.0 > a_box.2
+                        || a_box.1 > b_box.3
+                        || b_box.1 > a_box.3
+                    {
+                        continue;
+                    }
+                    //two big shapes can share more than one cell. only count the pair in the cell
+                    //where their overlap starts, so it isn't reported twice
+                    let (col, row) = self.cell_of(a_box.0.max(b_box.0), a_box.1.max(b_box.1));
+                    if row * self.cols + col != index {
+                        continue;
+                    }
+                    let (a, b) = (a.min(b), a.max(b));
+                    if test(a, b) {
+                        out.push((a, b));
+                    }
+                }
+            }
+        }
+    }
+}
+
+//test every shape against every other one. what the grid replaced, kept for comparison
+pub fn brute_force_pairs(shapes: &[Shape], out: &mut Vec<(usize, usize)>) {
+    for (i, a) in shapes.iter().enumerate() {
+        for (j, b) in shapes.iter().enumerate().skip(i + 1) {
+            if a.touches(b) {
+                out.push((i, j));
+            }
+        }
+    }
+}
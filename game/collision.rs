@@ -0,0 +1,320 @@
+//collision shapes and the tests between them
+//shapes are stored on entities relative to their center, then moved to where the entity is when testing
+
+use crate::entity::{Entity, EntityType};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shape {
+    //everything within radius of (x, y)
+    Circle {
+        x: f32,
+        y: f32,
+        radius: f32,
+    },
+    //axis aligned box centered on (x, y)
+    Aabb {
+        x: f32,
+        y: f32,
+        half_width: f32,
+        half_height: f32,
+    },
+}
+
+impl Shape {
+    //the same shape moved over by (dx, dy)
+    pub fn translated(&self, dx: f32, dy: f32) -> Shape {
+        match *self {
+            Shape::Circle { x, y, radius } => Shape::Circle {
+                x: x + dx,
+                y: y + dy,
+                radius,
+            },
+            Shape::Aabb {
+                x,
+                y,
+                half_width,
+                half_height,
+            } => Shape::Aabb {
+                x: x + dx,
+                y: y + dy,
+                half_width,
+                half_height,
+            },
+        }
+    }
+
+    //the same shape grown or shrunk around its center
+    pub fn scaled(&self, factor: f32) -> Shape {
+        match *self {
+            Shape::Circle { x, y, radius } => Shape::Circle {
+                x: x * factor,
+                y: y * factor,
+                radius: radius * factor,
+            },
+            Shape::Aabb {
+                x,
+                y,
+                half_width,
+                half_height,
+            } => Shape::Aabb {
+                x: x * factor,
+                y: y * factor,
+                half_width: half_width * factor,
+                half_height: half_height * factor,
+            },
+        }
+    }
+
+    //the smallest box that holds the whole shape, as (left, top, right, bottom)
+    pub fn bounding_box(&self) -> (f32, f32, f32, f32) {
+        match *self {
+            Shape::Circle { x, y, radius } => (x - radius, y - radius, x + radius, y + radius),
+            Shape::Aabb {
+                x,
+                y,
+                half_width,
+                half_height,
+            } => (
+                x - half_width,
+                y - half_height,
+                x + half_width,
+                y + half_height,
+            ),
+        }
+    }
+
+    //true if the two shapes overlap. shapes that only touch edges count as overlapping
+    pub fn touches(&self, other: &Shape) -> bool {
+        match (*self, *other) {
+            (
+                Shape::Circle {
+                    x: ax,
+                    y: ay,
+                    radius: ar,
+                },
+                Shape::Circle {
+                    x: bx,
+                    y: by,
+                    radius: br,
+                },
+            ) => {
+                let (dx, dy) = (bx - ax, by - ay);
+                dx * dx + dy * dy <= (ar + br) * (ar + br)
+            }
+            (Shape::Aabb { .. }, Shape::Aabb { .. }) => {
+                let (al, at, ar, ab) = self.bounding_box();
+                let (bl, bt, br, bb) = other.bounding_box();
+                al <= br && bl <= ar && at <= bb && bt <= ab
+            }
+            (Shape::Circle { x, y, radius }, aabb @ Shape::Aabb { .. })
+            | (aabb @ Shape::Aabb { .. }, Shape::Circle { x, y, radius }) => {
+                //find the point in the box closest to the circle's center
+                let (left, top, right, bottom) = aabb.bounding_box();
+                let dx = x - x.clamp(left, right);
+                let dy = y - y.clamp(top, bottom);
+                dx * dx + dy * dy <= radius * radius
+            }
+        }
+    }
+}
+
+//true if a moving shape touches another shape at any point along its path this tick
+//shape is where the moving shape ended up, after moving by (dx, dy)
+//the other shape is treated as standing still, so if it moved too, pass the difference between the two movements
+//circles against circles are exact. anything involving a box treats both shapes as boxes
+pub fn swept_touches(shape: &Shape, dx: f32, dy: f32, other: &Shape) -> bool {
+    if shape.touches(other) {
+        return true;
+    }
+    if dx == 0.0 && dy == 0.0 {
+        return false;
+    }
+    let start = shape.translated(-dx, -dy);
+    match (start, *other) {
+        (
+            Shape::Circle { x, y, radius },
+            Shape::Circle {
+                x: ox,
+                y: oy,
+                radius: or,
+            },
+        ) => segment_hits_circle(x, y, dx, dy, ox, oy, radius + or),
+        _ => {
+            //grow the other box by half the size of the moving one, then trace the moving one's center
+            let (left, top, right, bottom) = start.bounding_box();
+            let (half_width, half_height) = ((right - left) / 2.0, (bottom - top) / 2.0);
+            let (ol, ot, or, ob) = other.bounding_box();
+            segment_hits_box(
+                left + half_width,
+                top + half_height,
+                dx,
+                dy,
+                (
+                    ol - half_width,
+                    ot - half_height,
+                    or + half_width,
+                    ob + half_height,
+                ),
+            )
+        }
+    }
+}
+
+//true if the segment from (x, y) to (x + dx, y + dy) passes within radius of (cx, cy)
+fn segment_hits_circle(x: f32, y: f32, dx: f32, dy: f32, cx: f32, cy: f32, radius: f32) -> bool {
+    //find the point on the segment closest to the circle's center
+    let length_squared = dx * dx + dy * dy;
+    let t = (((cx - x) * dx + (cy - y) * dy) / length_squared).clamp(0.0, 1.0);
+    let (px, py) = (x + dx * t - cx, y + dy * t - cy);
+    px * px + py * py <= radius * radius
+}
+
+//true if the segment from (x, y) to (x + dx, y + dy) passes through a box given as (left, top, right, bottom)
+fn segment_hits_box(x: f32, y: f32, dx: f32, dy: f32, b: (f32, f32, f32, f32)) -> bool {
+    segment_enters_box(x, y, dx, dy, b).is_some()
+}
+
+//how far along the segment from (x, y) to (x + dx, y + dy) it first enters a box given as (left, top, right, bottom)
+//0 is the start of the segment, 1 the end. None if it misses the box
+fn segment_enters_box(x: f32, y: f32, dx: f32, dy: f32, b: (f32, f32, f32, f32)) -> Option<f32> {
+    //narrow down the part of the segment that is inside the box, one axis at a time
+    let mut t_min: f32 = 0.0;
+    let mut t_max: f32 = 1.0;
+    for (start, delta, low, high) in [(x, dx, b.0, b.2), (y, dy, b.1, b.3)] {
+        if delta == 0.0 {
+            if start < low || start > high {
+                return None;
+            }
+        } else {
+            let t0 = (low - start) / delta;
+            let t1 = (high - start) / delta;
+            t_min = t_min.max(t0.min(t1));
+            t_max = t_max.min(t0.max(t1));
+            if t_min > t_max {
+                return None;
+            }
+        }
+    }
+    Some(t_min)
+}
+
+//how far along a ray from (x, y) a shape is first touched, if it is within length
+//the direction (dir_x, dir_y) should have a length of 1
+//radius is how wide the ray is, as if a circle that size were sliding along it. 0 is a thin line
+pub fn ray_distance(
+    (x, y): (f32, f32),
+    (dir_x, dir_y): (f32, f32),
+    length: f32,
+    radius: f32,
+    shape: &Shape,
+) -> Option<f32> {
+    match *shape {
+        Shape::Circle {
+            x: cx,
+            y: cy,
+            radius: r,
+        } => {
+            let r = r + radius;
+            let (fx, fy) = (x - cx, y - cy);
+            let c = fx * fx + fy * fy - r * r;
+            if c <= 0.0 {
+                //the ray starts inside the circle
+                return Some(0.0);
+            }
+            let b = fx * dir_x + fy * dir_y;
+            let discriminant = b * b - c;
+            if b > 0.0 || discriminant < 0.0 {
+                return None;
+            }
+            let t = -b - discriminant.sqrt();
+            if t <= length {
+                Some(t)
+            } else {
+                None
+            }
+        }
+        Shape::Aabb { .. } => {
+            let (left, top, right, bottom) = shape.bounding_box();
+            let t = segment_enters_box(
+                x,
+                y,
+                dir_x * length,
+                dir_y * length,
+                (left - radius, top - radius, right + radius, bottom + radius),
+            )?;
+            Some(t * length)
+        }
+    }
+}
+
+//build shapes around a model's vertices, which are stored as position (x, y, z) then normal (x, y, z)
+//returns (circle, box) in model units, relative to the model's origin
+pub fn shapes_from_vertices(vertices: &[f32]) -> (Shape, Shape) {
+    let mut min_x = f32::MAX;
+    let mut min_y = f32::MAX;
+    let mut max_x = f32::MIN;
+    let mut max_y = f32::MIN;
+    for v in vertices.chunks_exact(6) {
+        min_x = min_x.min(v[0]);
+        min_y = min_y.min(v[1]);
+        max_x = max_x.max(v[0]);
+        max_y = max_y.max(v[1]);
+    }
+    if min_x > max_x {
+        //no vertices, so nothing to collide with
+        let empty = Shape::Circle {
+            x: 0.0,
+            y: 0.0,
+            radius: 0.0,
+        };
+        return (empty, empty);
+    }
+
+    let x = (min_x + max_x) / 2.0;
+    let y = (min_y + max_y) / 2.0;
+    //the circle is centered on the box, and reaches the farthest vertex from that point
+    let mut radius_squared: f32 = 0.0;
+    for v in vertices.chunks_exact(6) {
+        let (dx, dy) = (v[0] - x, v[1] - y);
+        radius_squared = radius_squared.max(dx * dx + dy * dy);
+    }
+    (
+        Shape::Circle {
+            x,
+            y,
+            radius: radius_squared.sqrt(),
+        },
+        Shape::Aabb {
+            x,
+            y,
+            half_width: (max_x - min_x) / 2.0,
+            half_height: (max_y - min_y) / 2.0,
+        },
+    )
+}
+
+//true if two entities overlap
+pub fn touches(a: &Entity, b: &Entity) -> bool {
+    a.bounds().touches(&b.bounds())
+}
+
+//true if entities of these two types are allowed to hit each other
+//follows what the comments on EntityType say:
+//  pickups only collide with the player
+//  objects collide with everything else
+//  effects collide with everything but projectiles
+//  the player collides with enemies and their projectiles
+//  player projectiles collide with enemies
+pub fn collides(a: EntityType, b: EntityType) -> bool {
+    use EntityType::*;
+    match (a, b) {
+        (Pickup, Player) | (Player, Pickup) => true,
+        (Pickup, _) | (_, Pickup) => false,
+        (Object, _) | (_, Object) => true,
+        (Effect, other) | (other, Effect) => !other.is_projectile(),
+        (Player, Enemy) | (Enemy, Player) => true,
+        (Player, EnemyProjectile) | (EnemyProjectile, Player) => true,
+        (PlayerProjectile, Enemy) | (Enemy, PlayerProjectile) => true,
+        _ => false,
+    }
+}
@@ -0,0 +1,100 @@
+//difficulty curve: the game gets harder as the score goes up and as time goes on
+//score and time add up to a difficulty level, and each level makes waves bigger and more frequent,
+//and enemies tougher, quicker to fire and faster shooting
+
+use serde::Deserialize;
+
+//how difficulty builds up, as written in the stage file
+//every field defaults to 0, which leaves that part of the game the same the whole way through
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct Difficulty {
+    //points of score that make up one level. 0 means score doesn't count
+    pub score_per_level: f32,
+    //seconds of play that make up one level. 0 means time doesn't count
+    pub seconds_per_level: f32,
+    //the highest level difficulty can reach. 0 means there's no limit
+    pub max_level: f32,
+    //how much each level adds, as a fraction of the normal amount. 0.1 is 10% more per level
+    pub wave_size: f32,
+    pub spawn_rate: f32,
+    pub health: f32,
+    pub fire_rate: f32,
+    pub projectile_speed: f32,
+}
+
+//what the curve works out to at one point in the game, as multipliers on the normal values
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DifficultyScale {
+    pub level: f32,
+    //enemies in each wave
+    pub wave_size: f32,
+    //time between waves. this one goes down as difficulty goes up
+    pub spawn_interval: f32,
+    pub health: f32,
+    //shots per second. enemy weapon cooldowns are divided by this
+    pub fire_rate: f32,
+    pub projectile_speed: f32,
+}
+
+impl Difficulty {
+    //the difficulty level for a score and a number of seconds played
+    //levels aren't whole numbers, so difficulty goes up smoothly instead of in steps
+    pub fn level(&self, score: i32, seconds: f32) -> f32 {
+        let mut level = 0.0;
+        if self.score_per_level > 0.0 {
+            level += score.max(0) as f32 / self.score_per_level;
+        }
+        if self.seconds_per_level > 0.0 {
+            level += seconds.max(0.0) / self.seconds_per_level;
+        }
+        if self.max_level > 0.0 {
+            level = level.min(self.max_level);
+        }
+        level
+    }
+
+    //the multipliers for a score and a number of seconds played
+    pub fn scale(&self, score: i32, seconds: f32) -> DifficultyScale {
+        let level = self.level(score, seconds);
+        DifficultyScale {
+            level,
+            wave_size: 1.0 + self.wave_size * level,
+            spawn_interval: 1.0 / (1.0 + self.spawn_rate * level),
+            health: 1.0 + self.health * level,
+            fire_rate: 1.0 + self.fire_rate * level,
+            projectile_speed: 1.0 + self.projectile_speed * level,
+        }
+    }
+
+    //a description of what's wrong with the curve, if anything
+    pub fn problem(&self) -> Option<&'static str> {
+        let fields = [
+            self.score_per_level,
+            self.seconds_per_level,
+            self.max_level,
+            self.wave_size,
+            self.spawn_rate,
+            self.health,
+            self.fire_rate,
+            self.projectile_speed,
+        ];
+        if fields.iter().any(|f| *f < 0.0) {
+            Some("difficulty fields can't be negative")
+        } else {
+            None
+        }
+    }
+}
+
+impl DifficultyScale {
+    //how many enemies come in a wave that normally has count, never fewer than 1
+    pub fn wave_count(&self, count: u32) -> u32 {
+        ((count as f32 * self.wave_size).round() as u32).max(1)
+    }
+
+    //health for an enemy that normally has health, never less than 1
+    pub fn enemy_health(&self, health: i32) -> i32 {
+        ((health as f32 * self.health).round() as i32).max(1)
+    }
+}
@@ -0,0 +1,139 @@
+//emitters: bullet patterns that run on their own over time
+//an emitter is attached to an entity and fires volleys of projectiles from it every so often,
+//turning as it goes, so spirals, rings and rotating patterns come out of a handful of numbers
+
+use crate::model::ModelId;
+use std::rc::Rc;
+
+//a projectile's speed over its lifetime, as (seconds since it was fired, speed) points
+//speed is blended in a straight line between points, and holds at the first and last ones
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SpeedCurve {
+    pub points: Vec<(f32, f32)>,
+}
+
+impl SpeedCurve {
+    //a curve that stays at one speed
+    pub fn constant(speed: f32) -> SpeedCurve {
+        SpeedCurve {
+            points: vec![(0.0, speed)],
+        }
+    }
+
+    //speed at a point in the projectile's life
+    pub fn speed_at(&self, age: f32) -> f32 {
+        let (first, last) = match (self.points.first(), self.points.last()) {
+            (Some(first), Some(last)) => (*first, *last),
+            _ => return 0.0,
+        };
+        if age <= first.0 {
+            return first.1;
+        }
+        for pair in self.points.windows(2) {
+            let ((t0, s0), (t1, s1)) = (pair[0], pair[1]);
+            if age <= t1 {
+                if t1 <= t0 {
+                    return s1;
+                }
+                return s0 + (s1 - s0) * (age - t0) / (t1 - t0);
+            }
+        }
+        last.1
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Emitter {
+    //the model the projectiles use
+    pub model: ModelId,
+    //projectiles fired in each volley
+    pub arms: u32,
+    //degrees the arms are spread over. 360 or more spaces them evenly all the way around
+    pub arc: f32,
+    //degrees the pattern is turned, clockwise from straight down the screen
+    pub offset: f32,
+    //degrees per second the pattern turns. negative turns it counterclockwise
+    pub rotation_speed: f32,
+    //seconds between volleys
+    pub interval: f32,
+    //volleys in a row before pausing. 0 never pauses
+    pub burst: u32,
+    //seconds to wait after each burst
+    pub burst_pause: f32,
+    //how fast each projectile goes over its life. shared with every projectile it fires
+    pub speed: Rc<SpeedCurve>,
+    //damage and punch through for each projectile, like a weapon's
+    pub damage: i32,
+    pub punch_through: i32,
+    //seconds until the next volley
+    pub timer: f32,
+    //degrees the pattern has turned so far
+    pub angle: f32,
+    //volleys fired in the current burst
+    pub fired: u32,
+}
+
+impl Emitter {
+    //an emitter firing a ring of arms every interval, at a constant speed
+    //change the other fields to make it turn, spread, burst or change speed
+    pub fn new(model: ModelId, arms: u32, interval: f32, speed: f32) -> Emitter {
+        Emitter {
+            model,
+            arms,
+            arc: 360.0,
+            offset: 0.0,
+            rotation_speed: 0.0,
+            interval,
+            burst: 0,
+            burst_pause: 0.0,
+            speed: Rc::new(SpeedCurve::constant(speed)),
+            damage: 1,
+            punch_through: 1,
+            timer: 0.0,
+            angle: 0.0,
+            fired: 0,
+        }
+    }
+
+    //the direction of each arm right now, in radians clockwise from straight down
+    pub fn arm_angles(&self) -> Vec<f32> {
+        let start = self.offset + self.angle;
+        let angles: Vec<f32> = if self.arc >= 360.0 {
+            let step = 360.0 / self.arms.max(1) as f32;
+            (0..self.arms).map(|i| start + step * i as f32).collect()
+        } else if self.arms <= 1 {
+            vec![start]
+        } else {
+            let step = self.arc / (self.arms - 1) as f32;
+            (0..self.arms)
+                .map(|i| start - self.arc / 2.0 + step * i as f32)
+                .collect()
+        };
+        angles.into_iter().map(|a| a.to_radians()).collect()
+    }
+
+    //advance the emitter by one tick
+    //returns the direction of every projectile to fire this tick, in radians clockwise from straight down
+    pub fn update(&mut self, dt: f32) -> Vec<f32> {
+        let mut out = Vec::new();
+        self.timer -= dt;
+        //short intervals can have more than one volley due in a single tick
+        //an interval of zero would never stop, so it fires once per tick instead
+        while self.timer <= 0.0 {
+            out.extend(self.arm_angles());
+            self.fired += 1;
+            if self.burst > 0 && self.fired >= self.burst {
+                self.fired = 0;
+                self.timer += self.interval + self.burst_pause;
+            } else {
+                self.timer += self.interval;
+            }
+            if self.interval <= 0.0 && self.timer <= 0.0 {
+                self.timer = 0.0;
+                break;
+            }
+        }
+        self.angle = (self.angle + self.rotation_speed * dt) % 360.0;
+        out
+    }
+}
@@ -0,0 +1,154 @@
+//enemy archetypes, loaded from a data file
+//each archetype is a kind of enemy: what it looks like, how tough it is, what it fires and what it drops
+
+use crate::entity::{PickupKind, WeaponType};
+use crate::model::{find_model, ModelData, ModelId};
+use crate::weapon::{find_weapon, WeaponDef};
+use serde::Deserialize;
+use std::path::Path;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EnemyDef {
+    //what the archetype is called in the data file
+    pub name: String,
+    //name of the model the enemy uses
+    pub model: String,
+    pub health: i32,
+    //name of the weapon the enemy fires, if it has one
+    #[serde(default)]
+    pub weapon: Option<String>,
+    //chance of leaving a pickup behind when destroyed, from 0 (never) to 1 (always)
+    #[serde(default)]
+    pub drop_chance: f32,
+    //what the pickup can be. one is picked at random each drop
+    //"upgrade" raises the player's weapon level. anything else is the name of a weapon to swap to
+    #[serde(default)]
+    pub drops: Vec<String>,
+    //name of the model its pickups use, shrunk down. the enemy's own model if there isn't one
+    #[serde(default)]
+    pub pickup_model: Option<String>,
+    //the model, found by its name. filled in when loading
+    #[serde(skip)]
+    pub model_id: ModelId,
+    //the pickup model, found by its name. filled in when loading
+    #[serde(skip)]
+    pub pickup_model_id: ModelId,
+    //the weapon, ready to give to the enemy. filled in when loading
+    #[serde(skip)]
+    pub wtype: WeaponType,
+    //the drops, ready to put on pickups. filled in when loading
+    #[serde(skip)]
+    pub pickups: Vec<PickupKind>,
+}
+
+//the layout of the enemies file: a list of [[enemy]] tables
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EnemyFile {
+    enemy: Vec<EnemyDef>,
+}
+
+//read enemy archetypes from the text of an enemies file, matching them against the loaded models and weapons
+pub fn parse_enemies(
+    text: &str,
+    models: &[ModelData],
+    weapons: &[WeaponDef],
+) -> Result<Vec<EnemyDef>, String> {
+    let file: EnemyFile = match toml::from_str(text) {
+        Ok(f) => f,
+        Err(error) => return Err(error.to_string()),
+    };
+    let mut enemies = file.enemy;
+    if enemies.is_empty() {
+        return Err("there must be at least one enemy".to_string());
+    }
+    for i in 0..enemies.len() {
+        let e = &mut enemies[i];
+        let problem = if e.health < 1 {
+            Some("health must be at least 1".to_string())
+        } else if !(0.0..=1.0).contains(&e.drop_chance) {
+            Some("drop_chance must be from 0 to 1".to_string())
+        } else if e.drop_chance > 0.0 && e.drops.is_empty() {
+            Some("drop_chance is set, but there are no drops".to_string())
+        } else {
+            None
+        };
+        if let Some(problem) = problem {
+            return Err(format!("enemy \"{}\": {}", e.name, problem));
+        }
+
+        e.model_id = match find_model(models, &e.model) {
+            Some(id) => id,
+            None => {
+                return Err(format!(
+                    "enemy \"{}\" uses model \"{}\", which was not loaded",
+                    e.name, e.model
+                ))
+            }
+        };
+        e.pickup_model_id = match &e.pickup_model {
+            None => e.model_id,
+            Some(name) => match find_model(models, name) {
+                Some(id) => id,
+                None => {
+                    return Err(format!(
+                        "enemy \"{}\" drops pickups with model \"{}\", which was not loaded",
+                        e.name, name
+                    ))
+                }
+            },
+        };
+        if let Some(name) = &e.weapon {
+            e.wtype = match find_weapon(weapons, name) {
+                Some(id) => weapons[id.0].equip(id),
+                None => {
+                    return Err(format!(
+                        "enemy \"{}\" uses weapon \"{}\", which is not in the weapons file",
+                        e.name, name
+                    ))
+                }
+            };
+        }
+        for name in &e.drops {
+            let kind = if name == "upgrade" {
+                PickupKind::Upgrade
+            } else {
+                match find_weapon(weapons, name) {
+                    Some(id) => PickupKind::Weapon(id),
+                    None => {
+                        return Err(format!(
+                            "enemy \"{}\" drops \"{}\", which is not \"upgrade\" or a weapon in the weapons file",
+                            e.name, name
+                        ))
+                    }
+                }
+            };
+            e.pickups.push(kind);
+        }
+
+        if enemies[..i]
+            .iter()
+            .any(|other| other.name == enemies[i].name)
+        {
+            return Err(format!("enemy \"{}\" is defined twice", enemies[i].name));
+        }
+    }
+    Ok(enemies)
+}
+
+//load the enemies file
+pub fn load_enemies(
+    path: &Path,
+    models: &[ModelData],
+    weapons: &[WeaponDef],
+) -> Result<Vec<EnemyDef>, String> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(t) => t,
+        Err(error) => return Err(format!("{} could not be read: {:?}", path.display(), error)),
+    };
+    match parse_enemies(&text, models, weapons) {
+        Ok(enemies) => Ok(enemies),
+        Err(error) => Err(format!("{}: {}", path.display(), error)),
+    }
+}
@@ -0,0 +1,431 @@
+use crate::collision::Shape;
+use crate::emitter::{Emitter, SpeedCurve};
+use crate::model::ModelId;
+use crate::patterns::Pattern;
+use crate::playfield::Playfield;
+use crate::pool::EntityHandle;
+use crate::weapon::{WeaponDef, WeaponId};
+use std::rc::Rc;
+
+//
+// Begin game entity definitions
+//
+
+//defines each entity type that will be used
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntityType {
+    Player, //the entity that the player controls. can collide with enemies and their projectiles
+    PlayerProjectile, //any projectile that the player entity spawns. can collide with enemies
+    Enemy, //any non-player, non-projectile entity. can collide with the player and their projectiles
+    EnemyProjectile, //projectiles spawned by enemies. can collide with the player
+    Object, //objects that are none of the above. collides with everything
+    Effect, //miscellaneous effects. collides with everything but projectiles
+    Pickup, //power ups left behind by enemies. only the player can touch them
+}
+
+impl EntityType {
+    //projectiles are immune to collision damage. their health counts how many things they can hit instead
+    pub fn is_projectile(&self) -> bool {
+        matches!(
+            self,
+            EntityType::PlayerProjectile | EntityType::EnemyProjectile
+        )
+    }
+}
+
+//defines each weapon
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WeaponType {
+    #[default]
+    None, //no weapon. some entities are unarmed
+    Projectile(WeaponId), //fires projectiles in the pattern given by a weapon definition
+    Beam(WeaponId),       //fires a beam while the fire key is held
+}
+
+impl WeaponType {
+    //which weapon definition the weapon uses, if the entity is armed
+    pub fn id(&self) -> Option<WeaponId> {
+        match self {
+            WeaponType::Projectile(id) | WeaponType::Beam(id) => Some(*id),
+            WeaponType::None => None,
+        }
+    }
+}
+
+//why an entity was removed from the game
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeathCause {
+    Destroyed,   //its health reached zero
+    OutOfBounds, //it wandered too far outside the playfield
+    Despawned,   //something removed it on purpose
+    Expired,     //it lived out its lifetime
+    Collected,   //it was a pickup, and the player took it
+}
+
+//what a pickup does to the player that touches it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PickupKind {
+    Upgrade,          //raises the level of the player's weapon
+    Weapon(WeaponId), //swaps the player's weapon for another one
+}
+
+//record of an entity being removed, produced at the end of the tick it died in
+//scoring and effects read these to react to deaths
+#[derive(Clone, Copy, Debug)]
+pub struct Death {
+    //the handle the entity had. it is stale by the time anything reads this
+    pub handle: EntityHandle,
+    pub etype: EntityType,
+    pub cause: DeathCause,
+    //whatever dealt the finishing blow, if anything
+    pub killer: Option<EntityHandle>,
+    //where the entity was when it died
+    pub center_x: f32,
+    pub center_y: f32,
+}
+
+//entities: anythign that is not UI nor background
+#[derive(Clone, Debug)]
+pub struct Entity {
+    //the classification of the entity. determines various behaviors
+    pub etype: EntityType,
+    //the equipped weapon. detemines what pattern is created when pressing the fire key
+    pub wtype: WeaponType,
+    //seconds until the weapon can fire again
+    pub weapon_cooldown: f32,
+    //shots left in the burst currently being fired, and seconds until the next one
+    pub burst_left: u32,
+    pub burst_timer: f32,
+    //trigger pulls since the weapon was last reloaded, and seconds left on the reload if it has started
+    pub ammo_used: u32,
+    pub reload_timer: f32,
+    //how hot the weapon is, from 0 to 1, and seconds left before it can fire again after overheating
+    pub heat: f32,
+    pub overheat_timer: f32,
+    //the graphical data of the entity
+    pub shape: ShipDraw,
+    //the area the entity can be hit in, relative to its center
+    pub hitbox: Shape,
+    //how much damage an entity can recieve without being destroyed
+    pub health: i32,
+    //current speed, in playfield units per second
+    pub speed_x: f32,
+    pub speed_y: f32,
+    //speed cannot exceed this value when thrusting, in units per second
+    pub top_speed: f32,
+    //how quickly thrusting builds up speed, in units per second per second
+    pub acceleration: f32,
+    //how quickly speed bleeds off, as a fraction per second. 0 means the entity coasts forever
+    pub drag: f32,
+    //damage for if two objects collide. projectiles are immune to this
+    pub collision_damage: i32,
+    //if false, ignore all collision regardless of class
+    pub is_tangible: bool,
+    //set once the entity has died. it stays in the entity list until the end of the tick
+    pub dead: Option<DeathCause>,
+    //whatever dealt the finishing blow
+    pub killer: Option<EntityHandle>,
+    //what a projectile has already hit, so it doesn't hit the same thing every tick while passing through it
+    pub hits: Vec<EntityHandle>,
+    //seconds since the entity was created
+    pub age: f32,
+    //fires patterns of projectiles by itself, separately from the weapon
+    pub emitter: Option<Emitter>,
+    //sets the entity's speed by its age, keeping it moving along heading (a direction of length 1)
+    pub speed_curve: Option<Rc<SpeedCurve>>,
+    pub heading: (f32, f32),
+    //how fast a homing projectile can turn toward its target, in radians per second. 0 means it doesn't home
+    pub turn_rate: f32,
+    //what a homing projectile is chasing. picked again whenever it's gone
+    pub target: Option<EntityHandle>,
+    //seconds the entity lasts before it is removed, if it doesn't last forever
+    pub lifetime: Option<f32>,
+    //damage a beam has built up that doesn't add up to a whole point yet
+    pub beam_damage: f32,
+    //seconds the fire key has been held while charging a shot
+    pub charge: f32,
+    //how far the weapon has been upgraded. 0 is the weapon as defined
+    pub weapon_level: u32,
+    //what the entity does when the player touches it, if it is a pickup
+    pub pickup: Option<PickupKind>,
+    //which enemy archetype the entity was spawned as, by its index in the list
+    pub archetype: Option<usize>,
+    //sets the entity's speed every tick, if it follows a movement pattern. keeps its own state, like where it stopped
+    pub pattern: Option<Pattern>,
+    //false until an enemy coming in from offscreen reaches the playfield
+    //it isn't removed for being out of bounds before then, unless it runs out of time
+    pub entered: bool,
+    //how old an enemy coming in from offscreen can get before it has to be in the playfield
+    pub entry_time: f32,
+}
+
+//functions called by entities
+impl Entity {
+    //move the entity along the screen
+    pub fn move_x(&mut self, d: f32) {
+        self.shape.center_x += d;
+    }
+    pub fn move_y(&mut self, d: f32) {
+        self.shape.center_y += d;
+    }
+
+    //count down the weapon cooldown by one tick
+    //it is allowed to dip a little below zero, so the leftover carries into the next shot and the fire rate stays exact
+    pub fn cool_down(&mut self, dt: f32) {
+        if self.weapon_cooldown > 0.0 {
+            self.weapon_cooldown -= dt;
+        }
+        if self.burst_left > 0 {
+            self.burst_timer -= dt;
+        }
+    }
+
+    //true while the weapon is reloading or has overheated, and can't fire
+    pub fn weapon_locked(&self) -> bool {
+        self.reload_timer > 0.0 || self.overheat_timer > 0.0
+    }
+
+    //use up ammo and build up heat for one trigger pull of a weapon
+    //running out starts a reload, and reaching full heat makes the weapon wait to cool off
+    pub fn spend_shot(&mut self, def: &WeaponDef, heat: f32) {
+        if def.ammo > 0 {
+            self.ammo_used += 1;
+            if self.ammo_used >= def.ammo {
+                self.reload_timer = def.reload_time;
+            }
+        }
+        if heat > 0.0 {
+            self.heat = (self.heat + heat).min(1.0);
+            if self.heat >= 1.0 {
+                self.overheat_timer = def.overheat_time;
+            }
+        }
+    }
+
+    //count down a reload or overheat by one tick, and let heat bleed off
+    pub fn cool_weapon(&mut self, def: &WeaponDef, dt: f32) {
+        if self.reload_timer > 0.0 {
+            self.reload_timer -= dt;
+            if self.reload_timer <= 0.0 {
+                self.reload_timer = 0.0;
+                self.ammo_used = 0;
+            }
+        }
+        if self.overheat_timer > 0.0 {
+            self.overheat_timer -= dt;
+            if self.overheat_timer <= 0.0 {
+                self.overheat_timer = 0.0;
+                self.heat = 0.0;
+            }
+        } else {
+            self.heat = (self.heat - def.cooling * dt).max(0.0);
+        }
+    }
+
+    //full ammo and no heat, like a weapon that was just picked up
+    pub fn reset_weapon(&mut self) {
+        self.weapon_cooldown = 0.0;
+        self.burst_left = 0;
+        self.charge = 0.0;
+        self.ammo_used = 0;
+        self.reload_timer = 0.0;
+        self.heat = 0.0;
+        self.overheat_timer = 0.0;
+    }
+
+    //push the entity in a direction for one tick, then apply drag and the speed limit
+    //the direction is normalized so moving diagonally is no faster than moving straight
+    pub fn thrust(&mut self, dir_x: f32, dir_y: f32, dt: f32) {
+        let length = (dir_x * dir_x + dir_y * dir_y).sqrt();
+        if length > 0.0 {
+            self.speed_x += dir_x / length * self.acceleration * dt;
+            self.speed_y += dir_y / length * self.acceleration * dt;
+        }
+
+        //decay exponentially so the same amount of speed is lost per second at any tick rate
+        let decay = (-self.drag * dt).exp();
+        self.speed_x *= decay;
+        self.speed_y *= decay;
+
+        let speed = (self.speed_x * self.speed_x + self.speed_y * self.speed_y).sqrt();
+        if speed > self.top_speed {
+            self.speed_x *= self.top_speed / speed;
+            self.speed_y *= self.top_speed / speed;
+        }
+    }
+
+    //set the entity's speed from its speed curve, if it has one
+    pub fn follow_speed_curve(&mut self) {
+        if let Some(curve) = &self.speed_curve {
+            let speed = curve.speed_at(self.age);
+            self.speed_x = self.heading.0 * speed;
+            self.speed_y = self.heading.1 * speed;
+        }
+    }
+
+    //turn the entity toward a point by no more than max_turn radians, keeping the same speed
+    pub fn steer_toward(&mut self, x: f32, y: f32, max_turn: f32) {
+        let (dx, dy) = (x - self.shape.center_x, y - self.shape.center_y);
+        let speed = (self.speed_x * self.speed_x + self.speed_y * self.speed_y).sqrt();
+        //something standing still still has a heading, if it follows a speed curve
+        let (vx, vy) = if speed > 0.0 {
+            (self.speed_x, self.speed_y)
+        } else {
+            self.heading
+        };
+        if (dx == 0.0 && dy == 0.0) || (vx == 0.0 && vy == 0.0) {
+            return;
+        }
+
+        let current = vy.atan2(vx);
+        //the shortest way around to the wanted angle, between -pi and pi
+        let mut turn = dy.atan2(dx) - current;
+        if turn > std::f32::consts::PI {
+            turn -= std::f32::consts::TAU;
+        } else if turn < -std::f32::consts::PI {
+            turn += std::f32::consts::TAU;
+        }
+        let (sin, cos) = (current + turn.clamp(-max_turn, max_turn)).sin_cos();
+        self.speed_x = cos * speed;
+        self.speed_y = sin * speed;
+        self.heading = (cos, sin);
+    }
+
+    //true once the entity has lived out its lifetime
+    pub fn is_expired(&self) -> bool {
+        match self.lifetime {
+            Some(lifetime) => self.age >= lifetime,
+            None => false,
+        }
+    }
+
+    //move the entity by its speed for one tick
+    pub fn integrate(&mut self, dt: f32) {
+        let (speed_x, speed_y) = (self.speed_x, self.speed_y);
+        self.move_x(speed_x * dt);
+        self.move_y(speed_y * dt);
+    }
+
+    //keep the entity inside the playfield, stopping any movement into the edge it hit
+    pub fn clamp_to(&mut self, playfield: &Playfield) {
+        let (x, y) = playfield.clamp(self.shape.center_x, self.shape.center_y);
+        if x != self.shape.center_x {
+            self.speed_x = 0.0;
+        }
+        if y != self.shape.center_y {
+            self.speed_y = 0.0;
+        }
+        self.shape.center_x = x;
+        self.shape.center_y = y;
+    }
+
+    //subtract health from an entity, marking it dead if its health reaches zero
+    //projectiles will use this to determine if they should be able to hit multiple entities, and if so how many
+    //source is whatever dealt the damage, and is recorded as the killer
+    pub fn damage(&mut self, damage: i32, source: Option<EntityHandle>) {
+        //subract the damage from the health of the entity
+        self.health -= damage;
+
+        //mark for removal if health is less than or equal to zero
+        if self.health <= 0 && self.is_alive() {
+            self.dead = Some(DeathCause::Destroyed);
+            self.killer = source;
+        }
+    }
+
+    //mark the entity for removal at the end of the tick. the first cause given sticks
+    pub fn kill(&mut self, cause: DeathCause) {
+        if self.is_alive() {
+            self.dead = Some(cause);
+        }
+    }
+
+    //the entity touched another one. takes the other's collision damage, unless this is a projectile,
+    //in which case it uses up one hit of its punch through instead
+    pub fn touch(&mut self, other_damage: i32, other: EntityHandle) {
+        if self.etype.is_projectile() {
+            self.hits.push(other);
+            self.damage(1, Some(other));
+        } else {
+            self.damage(other_damage, Some(other));
+        }
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.dead.is_none()
+    }
+
+    //the entity's hitbox at where the entity is now
+    pub fn bounds(&self) -> Shape {
+        self.hitbox
+            .translated(self.shape.center_x, self.shape.center_y)
+    }
+
+    //how far the entity moved during the current tick
+    pub fn motion(&self) -> (f32, f32) {
+        (
+            self.shape.center_x - self.shape.prev_x,
+            self.shape.center_y - self.shape.prev_y,
+        )
+    }
+
+    //a box covering everywhere the hitbox has been this tick
+    pub fn swept_bounds(&self) -> Shape {
+        let (dx, dy) = self.motion();
+        let (left, top, right, bottom) = self.bounds().bounding_box();
+        let (left, right) = (left.min(left - dx), right.max(right - dx));
+        let (top, bottom) = (top.min(top - dy), bottom.max(bottom - dy));
+        Shape::Aabb {
+            x: (left + right) / 2.0,
+            y: (top + bottom) / 2.0,
+            half_width: (right - left) / 2.0,
+            half_height: (bottom - top) / 2.0,
+        }
+    }
+}
+
+//stores data about what to draw
+#[derive(Clone, Debug)]
+pub struct ShipDraw {
+    pub center_x: f32,
+    pub center_y: f32,
+    //where the center was at the end of the previous tick. used to blend between ticks when drawing
+    pub prev_x: f32,
+    pub prev_y: f32,
+    //how big to draw the model, compared to its normal size
+    pub scale: f32,
+    //the model to draw. the mesh itself stays in the model list
+    pub model: ModelId,
+}
+
+impl ShipDraw {
+    //create the draw data for something sitting at a given point
+    pub fn new(model: ModelId, center_x: f32, center_y: f32) -> ShipDraw {
+        ShipDraw {
+            center_x,
+            center_y,
+            prev_x: center_x,
+            prev_y: center_y,
+            scale: 1.0,
+            model,
+        }
+    }
+
+    //remember the current position before a tick moves it
+    pub fn save_position(&mut self) {
+        self.prev_x = self.center_x;
+        self.prev_y = self.center_y;
+    }
+
+    //position to draw at, blended between the previous tick (alpha 0) and the current one (alpha 1)
+    pub fn interpolated(&self, alpha: f32) -> (f32, f32) {
+        (
+            self.prev_x + (self.center_x - self.prev_x) * alpha,
+            self.prev_y + (self.center_y - self.prev_y) * alpha,
+        )
+    }
+}
+
+//
+//End game entity definitions
+//
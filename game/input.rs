@@ -0,0 +1,36 @@
+//the controls the simulation reads each tick
+//the binary fills this in from the keyboard. tests can fill it in directly
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Input {
+    pub up: bool,
+    pub down: bool,
+    pub left: bool,
+    pub right: bool,
+    //fire is true while the key is held
+    //the pressed and released edges are true for the tick the key went down or came up,
+    //so a tap that starts and ends between two ticks is still seen
+    pub fire: bool,
+    pub fire_pressed: bool,
+    pub fire_released: bool,
+}
+
+impl Input {
+    //the direction being held, with each axis from -1 to 1. y points down the screen
+    pub fn direction(&self) -> (f32, f32) {
+        let mut x = 0.0;
+        let mut y = 0.0;
+        if self.up {
+            y -= 1.0;
+        }
+        if self.down {
+            y += 1.0;
+        }
+        if self.left {
+            x -= 1.0;
+        }
+        if self.right {
+            x += 1.0;
+        }
+        (x, y)
+    }
+}
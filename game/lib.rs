@@ -0,0 +1,39 @@
+//the game simulation: entities, spawning, weapons, collisions and scoring
+//nothing in here knows about windows, graphics or the keyboard, so it can be run and tested headless
+//the `program` binary reads the keyboard into an Input, steps the State, and draws the result
+
+pub mod beam;
+pub mod broadphase;
+pub mod collision;
+pub mod difficulty;
+pub mod emitter;
+pub mod enemy;
+pub mod entity;
+pub mod input;
+pub mod model;
+pub mod patterns;
+pub mod playfield;
+pub mod pool;
+pub mod rng;
+pub mod spline;
+pub mod stage;
+pub mod state;
+pub mod timestep;
+pub mod weapon;
+
+pub use beam::Beam;
+pub use collision::Shape;
+pub use difficulty::{Difficulty, DifficultyScale};
+pub use emitter::{Emitter, SpeedCurve};
+pub use enemy::EnemyDef;
+pub use entity::{Death, DeathCause, Entity, EntityType, PickupKind, ShipDraw, WeaponType};
+pub use input::Input;
+pub use model::{ModelData, ModelId};
+pub use playfield::Playfield;
+pub use pool::{EntityHandle, Pool};
+pub use rng::Rng;
+pub use spline::Spline;
+pub use stage::{Entry, Formation, Side, Stage, WaveEvent};
+pub use state::State;
+pub use timestep::FixedTimestep;
+pub use weapon::{WeaponDef, WeaponId, WeaponKind, WeaponLevel};
@@ -0,0 +1,82 @@
+use crate::collision::{shapes_from_vertices, Shape};
+use obj::load_obj;
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::path::Path;
+
+//how many playfield units one unit in a model file takes up
+pub const MODEL_SCALE: f32 = 20.0;
+
+//refers to one model in the list of loaded models
+//entities share models through these, instead of each one carrying its own copy of the mesh
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ModelId(pub usize);
+
+#[derive(Clone, Debug)]
+//contains data about 3D models, since the Obj package I'm using doesn't have everythin the way it needs to be in order for Notan to use it
+pub struct ModelData {
+    pub name: String,
+    pub vertices: Vec<f32>,
+    pub indices: Vec<u16>,
+    //collision shapes that fit around the model, in playfield units relative to the entity's center
+    pub circle: Shape,
+    pub aabb: Shape,
+}
+
+impl ModelData {
+    pub fn new(name: String, vertices: Vec<f32>, indices: Vec<u16>) -> ModelData {
+        let (circle, aabb) = shapes_from_vertices(&vertices);
+        ModelData {
+            name,
+            vertices,
+            indices,
+            circle: circle.scaled(MODEL_SCALE),
+            aabb: aabb.scaled(MODEL_SCALE),
+        }
+    }
+}
+
+//load a single model from anything that reads like an .obj file
+pub fn load_model<R: BufRead>(input: R) -> Result<ModelData, String> {
+    let ob: obj::Obj = match load_obj(input) {
+        Ok(o) => o,
+        Err(error) => return Err(format!("Object could not be processed {:?}", error)),
+    };
+    let mut vertices = Vec::new();
+    for v in ob.vertices {
+        vertices.extend_from_slice(&v.position);
+        vertices.extend_from_slice(&v.normal);
+    }
+    let name = match ob.name {
+        None => "unnamed".to_string(),
+        Some(out) => out,
+    };
+    Ok(ModelData::new(name, vertices, ob.indices))
+}
+
+//look up a model by the name it was given in its file
+pub fn find_model(models: &[ModelData], name: &str) -> Option<ModelId> {
+    models.iter().position(|m| m.name == name).map(ModelId)
+}
+
+//load every model in a directory
+pub fn load_models(dir: &Path) -> Result<Vec<ModelData>, String> {
+    let files = match std::fs::read_dir(dir) {
+        Ok(file) => file,
+        Err(error) => return Err(format!("Directory could not be read: {:?}", error)),
+    };
+    let mut models = Vec::new();
+    for f in files {
+        let file = match f {
+            Ok(m) => m,
+            Err(error) => return Err(format!("Error in reading directory: {:?}", error)),
+        };
+        let a = match File::open(file.path()) {
+            Ok(file) => file,
+            Err(error) => return Err(format!("File not opened: {:?}", error)),
+        };
+        models.push(load_model(BufReader::new(a))?);
+    }
+    Ok(models)
+}
@@ -0,0 +1,159 @@
+//enemy move patterns
+//each one works out an enemy's speed every tick from how long it has been alive,
+//so weaves and loops stay in step no matter how many ticks there are per second
+//patterns move relative to a heading: the way the enemy is going, like straight down for a wave from the top
+//except for paths, which go through points laid out in the stage file
+
+use crate::entity::Entity;
+use crate::spline::Spline;
+use std::f32::consts::TAU;
+
+//the kinds of movement
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Movement {
+    //stay perfectly still
+    Still,
+    //go straight along the heading
+    Straight,
+    //go along the heading, weaving from side to side by size, once every period
+    SineWeave,
+    //go along the heading while looping around in circles of radius size, once every period
+    Circle,
+    //dive in along the heading for period seconds, wait there as long again, then go back out
+    DiveAndRetreat,
+    //come in along the heading for period seconds, then stop and bob from side to side by size
+    StopAndHover,
+    //trace a figure eight size wide around a point that moves along the heading, once every period
+    FigureEight,
+    //follow the pattern's path at its speed, starting from wherever the enemy is
+    Path,
+}
+
+//a movement, set up for one enemy
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pattern {
+    pub movement: Movement,
+    //the way the pattern goes, with a length of 1
+    pub heading: (f32, f32),
+    //how fast it goes along the heading, in playfield units per second
+    pub speed: f32,
+    //how big the weaves, loops or bobbing are, in playfield units
+    pub size: f32,
+    //seconds for one weave, loop or figure eight, or how long a dive or approach lasts
+    pub period: f32,
+    //where a hovering enemy stopped, or where a path is moved to so it starts at the enemy
+    //filled in when it's first needed
+    pub anchor: Option<(f32, f32)>,
+    //the path to follow for path movement, by its index in the stage's paths
+    pub path: Option<usize>,
+}
+
+impl Pattern {
+    pub fn new(movement: Movement, heading: (f32, f32), speed: f32) -> Pattern {
+        Pattern {
+            movement,
+            heading,
+            speed,
+            size: 80.0,
+            period: 2.0,
+            anchor: None,
+            path: None,
+        }
+    }
+
+    //a pattern that follows a path at a speed
+    pub fn follow(path: usize, speed: f32) -> Pattern {
+        let mut pattern = Pattern::new(Movement::Path, (0.0, 1.0), speed);
+        pattern.path = Some(path);
+        pattern
+    }
+
+    //set the entity's speed for the tick ahead. age is how long it has been following the pattern
+    //paths are the stage's, which path patterns look theirs up in
+    pub fn update(&mut self, e: &mut Entity, paths: &[Spline], age: f32, dt: f32) {
+        let (hx, hy) = self.heading;
+        //to the left of the heading, so a heading down the screen has its side pointing right
+        let (sx, sy) = (-hy, hx);
+        let omega = TAU / self.period.max(0.001);
+        //curves use their speed halfway through the tick, so they close up properly at any tick rate
+        let mid = age + dt / 2.0;
+        if let (Movement::Path, Some(path)) = (self.movement, self.path.and_then(|i| paths.get(i)))
+        {
+            let (x, y) = (e.shape.center_x, e.shape.center_y);
+            let (start_x, start_y) = path.start();
+            let (ax, ay) = *self.anchor.get_or_insert((x - start_x, y - start_y));
+            //head for where the path is at the end of the tick, so no drift builds up
+            let (px, py) = path.at_distance(self.speed * (age + dt));
+            let dt = dt.max(0.0001);
+            e.speed_x = (ax + px - x) / dt;
+            e.speed_y = (ay + py - y) / dt;
+            return;
+        }
+        //how fast to go forward and to the side
+        let (forward, side) = match self.movement {
+            Movement::Still => (0.0, 0.0),
+            Movement::Straight => (self.speed, 0.0),
+            //the speed of a point at size * sin(omega * age) to the side
+            Movement::SineWeave => (self.speed, self.size * omega * (omega * mid).cos()),
+            Movement::Circle => {
+                let (sin, cos) = (omega * mid).sin_cos();
+                (
+                    self.speed + self.size * omega * cos,
+                    -self.size * omega * sin,
+                )
+            }
+            Movement::DiveAndRetreat => {
+                if age < self.period {
+                    (self.speed, 0.0)
+                } else if age < self.period * 2.0 {
+                    (0.0, 0.0)
+                } else {
+                    (-self.speed, 0.0)
+                }
+            }
+            Movement::StopAndHover => {
+                if age < self.period {
+                    (self.speed, 0.0)
+                } else {
+                    let (x, y) = (e.shape.center_x, e.shape.center_y);
+                    let (ax, ay) = *self.anchor.get_or_insert((x, y));
+                    //bob around the anchor, steering back onto the path so no drift builds up
+                    let t = age - self.period + dt;
+                    let target_side = self.size * (omega * t).sin();
+                    let (dx, dy) = (ax + sx * target_side - x, ay + sy * target_side - y);
+                    let dt = dt.max(0.0001);
+                    ((dx * hx + dy * hy) / dt, (dx * sx + dy * sy) / dt)
+                }
+            }
+            //side to side once per period, and forward and back twice, so the path crosses itself
+            Movement::FigureEight => (
+                self.speed + self.size / 2.0 * omega * (2.0 * omega * mid).cos(),
+                self.size / 2.0 * omega * (omega * mid).cos(),
+            ),
+            //a path pattern without a path has nowhere to go
+            Movement::Path => (0.0, 0.0),
+        };
+        e.speed_x = hx * forward + sx * side;
+        e.speed_y = hy * forward + sy * side;
+    }
+}
+
+//look up a movement by the name stage files use for it
+//the names of the old straight line patterns also say which way they go
+pub fn find_movement(name: &str) -> Option<(Movement, Option<(f32, f32)>)> {
+    let found = match name {
+        "still" => (Movement::Still, None),
+        "straight" => (Movement::Straight, None),
+        "left_to_right" => (Movement::Straight, Some((1.0, 0.0))),
+        "right_to_left" => (Movement::Straight, Some((-1.0, 0.0))),
+        "top_to_bottom" => (Movement::Straight, Some((0.0, 1.0))),
+        "bottom_to_top" => (Movement::Straight, Some((0.0, -1.0))),
+        "sine" => (Movement::SineWeave, None),
+        "circle" => (Movement::Circle, None),
+        "dive" => (Movement::DiveAndRetreat, None),
+        "hover" => (Movement::StopAndHover, None),
+        "figure_eight" => (Movement::FigureEight, None),
+        _ => return None,
+    };
+    Some(found)
+}
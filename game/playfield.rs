@@ -0,0 +1,61 @@
+//the rectangle the game is played in
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Playfield {
+    pub left: f32,
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+}
+
+impl Playfield {
+    //create a playfield with its top left corner at the origin
+    pub fn new(width: f32, height: f32) -> Playfield {
+        Playfield {
+            left: 0.0,
+            top: 0.0,
+            right: width,
+            bottom: height,
+        }
+    }
+
+    pub fn width(&self) -> f32 {
+        self.right - self.left
+    }
+
+    pub fn height(&self) -> f32 {
+        self.bottom - self.top
+    }
+
+    //true if the point is inside the playfield grown by a margin on every side
+    pub fn contains(&self, x: f32, y: f32, margin: f32) -> bool {
+        x >= self.left - margin
+            && x <= self.right + margin
+            && y >= self.top - margin
+            && y <= self.bottom + margin
+    }
+
+    //how far (x, y) is outside the playfield, along whichever axis it is furthest out on. 0 if it is inside
+    pub fn distance_outside(&self, x: f32, y: f32) -> f32 {
+        (self.left - x)
+            .max(x - self.right)
+            .max(self.top - y)
+            .max(y - self.bottom)
+            .max(0.0)
+    }
+
+    //the closest point to (x, y) that is inside the playfield
+    pub fn clamp(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            x.clamp(self.left, self.right),
+            y.clamp(self.top, self.bottom),
+        )
+    }
+}
+
+impl Default for Playfield {
+    //the size of the game window
+    fn default() -> Playfield {
+        Playfield::new(1000.0, 600.0)
+    }
+}
@@ -0,0 +1,172 @@
+//pool that stores the entities in the game, packed together so going through them is fast
+//values sit next to each other in one list. releasing one moves the last value into its place,
+//so acquiring and releasing never shift the list, and the space is kept around for the next value
+//each slot has a generation that is bumped when its value is released,
+//so a handle to a released entity can never be mistaken for whatever takes its place
+
+//refers to one entity in the pool
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct EntityHandle {
+    //which slot the entity lives in
+    pub index: u32,
+    //which use of that slot the handle refers to
+    pub generation: u32,
+}
+
+#[derive(Clone, Debug)]
+struct PoolSlot {
+    generation: u32,
+    //where the slot's value is in the packed list, if it has one
+    position: Option<u32>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Pool<T> {
+    //the live values, packed together in no particular order
+    items: Vec<T>,
+    //the handle of each value, lined up with items
+    owners: Vec<EntityHandle>,
+    slots: Vec<PoolSlot>,
+    //indices of slots without a value, reused before adding more slots
+    free: Vec<u32>,
+}
+
+impl<T> Pool<T> {
+    pub fn new() -> Pool<T> {
+        Pool {
+            items: Vec::new(),
+            owners: Vec::new(),
+            slots: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    //a pool with room for this many values before it has to grow
+    pub fn with_capacity(capacity: usize) -> Pool<T> {
+        Pool {
+            items: Vec::with_capacity(capacity),
+            owners: Vec::with_capacity(capacity),
+            slots: Vec::with_capacity(capacity),
+            free: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    //how many values fit before the pool has to grow
+    pub fn capacity(&self) -> usize {
+        self.items.capacity()
+    }
+
+    //add a value to the end of the packed list, returning the handle it can be found with
+    pub fn acquire(&mut self, value: T) -> EntityHandle {
+        let position = self.items.len() as u32;
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None => {
+                self.slots.push(PoolSlot {
+                    generation: 0,
+                    position: None,
+                });
+                self.slots.len() as u32 - 1
+            }
+        };
+        let slot = &mut self.slots[index as usize];
+        slot.position = Some(position);
+        let handle = EntityHandle {
+            index,
+            generation: slot.generation,
+        };
+        self.items.push(value);
+        self.owners.push(handle);
+        handle
+    }
+
+    //take a value out of the pool. the last value is moved into the gap it leaves
+    //returns None if the handle is stale
+    pub fn release(&mut self, handle: EntityHandle) -> Option<T> {
+        let position = self.position(handle)?;
+        let slot = &mut self.slots[handle.index as usize];
+        slot.generation = slot.generation.wrapping_add(1);
+        slot.position = None;
+        self.free.push(handle.index);
+
+        let value = self.items.swap_remove(position);
+        self.owners.swap_remove(position);
+        //whatever was last now lives where the released value was
+        if let Some(moved) = self.owners.get(position) {
+            self.slots[moved.index as usize].position = Some(position as u32);
+        }
+        Some(value)
+    }
+
+    //where a handle's value is in the packed list. None if the handle is stale
+    fn position(&self, handle: EntityHandle) -> Option<usize> {
+        let slot = self.slots.get(handle.index as usize)?;
+        if slot.generation != handle.generation {
+            return None;
+        }
+        slot.position.map(|p| p as usize)
+    }
+
+    //true if the handle still refers to a live value
+    pub fn contains(&self, handle: EntityHandle) -> bool {
+        self.position(handle).is_some()
+    }
+
+    pub fn get(&self, handle: EntityHandle) -> Option<&T> {
+        let position = self.position(handle)?;
+        self.items.get(position)
+    }
+
+    pub fn get_mut(&mut self, handle: EntityHandle) -> Option<&mut T> {
+        let position = self.position(handle)?;
+        self.items.get_mut(position)
+    }
+
+    //every live value along with its handle
+    pub fn iter(&self) -> impl Iterator<Item = (EntityHandle, &T)> {
+        self.owners.iter().copied().zip(self.items.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (EntityHandle, &mut T)> {
+        self.owners.iter().copied().zip(self.items.iter_mut())
+    }
+
+    //every live value, without handles
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.items.iter()
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.items.iter_mut()
+    }
+
+    //handles of every live value
+    pub fn handles(&self) -> Vec<EntityHandle> {
+        self.owners.clone()
+    }
+
+    //release every value the function returns false for
+    pub fn retain<F: FnMut(EntityHandle, &T) -> bool>(&mut self, mut keep: F) {
+        //going backwards, whatever gets moved into a gap has already been checked
+        for position in (0..self.items.len()).rev() {
+            let handle = self.owners[position];
+            if !keep(handle, &self.items[position]) {
+                self.release(handle);
+            }
+        }
+    }
+}
+
+impl<T> Default for Pool<T> {
+    fn default() -> Pool<T> {
+        Pool::new()
+    }
+}
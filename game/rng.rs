@@ -0,0 +1,34 @@
+//small random number generator
+//the same seed always gives the same numbers, so games and tests can be replayed exactly
+
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u32,
+}
+
+impl Rng {
+    pub fn new(seed: u32) -> Rng {
+        Rng { state: seed }
+    }
+
+    //a number from 0 up to but not including 1
+    pub fn next_f32(&mut self) -> f32 {
+        self.state = self.state.wrapping_mul(1664525).wrapping_add(1013904223);
+        (self.state >> 8) as f32 / (1 << 24) as f32
+    }
+
+    //a number from low up to but not including high
+    pub fn range(&mut self, low: f32, high: f32) -> f32 {
+        low + (high - low) * self.next_f32()
+    }
+
+    //a whole number from 0 up to but not including count
+    pub fn below(&mut self, count: usize) -> usize {
+        ((self.next_f32() * count as f32) as usize).min(count.saturating_sub(1))
+    }
+
+    //true with the given chance, from 0 (never) to 1 (always)
+    pub fn chance(&mut self, chance: f32) -> bool {
+        self.next_f32() < chance
+    }
+}
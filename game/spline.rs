@@ -0,0 +1,198 @@
+//splines: paths through the playfield for enemies to follow, like the swoops enemies make on their way in
+//a spline is built from a list of points, either as a Catmull-Rom spline that passes through every point,
+//or as cubic Bezier curves joined end to end
+//paths are measured along their length when they're made, so enemies go along them at a steady speed
+
+use serde::Deserialize;
+
+//points worked out along each curve to measure it. enemies go in straight lines between them
+const SAMPLES_PER_CURVE: usize = 32;
+
+//how the points make a curve
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Curve {
+    //a smooth curve through every point
+    #[default]
+    CatmullRom,
+    //cubic Bezier curves: a start point, then two control points and an end point for each curve
+    //each curve starts where the last one ended
+    Bezier,
+}
+
+//what happens when an enemy gets to the end of a path
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PathEnd {
+    //keep going straight the way the path was heading, until it leaves the playfield
+    #[default]
+    Exit,
+    //go around again from the start. Catmull-Rom paths close up by themselves,
+    //Bezier paths have to end where they start
+    Loop,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Spline {
+    pub name: String,
+    pub curve: Curve,
+    pub points: Vec<(f32, f32)>,
+    pub end: PathEnd,
+    //points along the path, and how far along it each one is
+    samples: Vec<(f32, f32)>,
+    distances: Vec<f32>,
+}
+
+impl Spline {
+    //make a path, or say what's wrong with its points
+    pub fn new(
+        name: &str,
+        curve: Curve,
+        points: Vec<(f32, f32)>,
+        end: PathEnd,
+    ) -> Result<Spline, String> {
+        let curves = match curve {
+            Curve::CatmullRom => {
+                if points.len() < 2 {
+                    return Err("Catmull-Rom paths need at least 2 points".to_string());
+                }
+                match end {
+                    PathEnd::Exit => points.len() - 1,
+                    PathEnd::Loop => points.len(),
+                }
+            }
+            Curve::Bezier => {
+                if points.len() < 4 || !(points.len() - 1).is_multiple_of(3) {
+                    return Err(
+                        "Bezier paths need a start point, then 3 points for each curve".to_string(),
+                    );
+                }
+                if end == PathEnd::Loop && points.first() != points.last() {
+                    return Err("looping Bezier paths have to end where they start".to_string());
+                }
+                (points.len() - 1) / 3
+            }
+        };
+
+        let mut path = Spline {
+            name: name.to_string(),
+            curve,
+            points,
+            end,
+            samples: Vec::new(),
+            distances: Vec::new(),
+        };
+        let mut travelled = 0.0;
+        for i in 0..curves {
+            //each curve's end is the next one's start, so only the last curve adds its end
+            for step in 0..SAMPLES_PER_CURVE {
+                let point = path.point_on(i, step as f32 / SAMPLES_PER_CURVE as f32);
+                if let Some(last) = path.samples.last() {
+                    travelled += distance(*last, point);
+                }
+                path.samples.push(point);
+                path.distances.push(travelled);
+            }
+        }
+        let point = path.point_on(curves - 1, 1.0);
+        travelled += distance(*path.samples.last().unwrap(), point);
+        path.samples.push(point);
+        path.distances.push(travelled);
+        Ok(path)
+    }
+
+    //where the path starts
+    pub fn start(&self) -> (f32, f32) {
+        self.samples[0]
+    }
+
+    //how long the path is, in playfield units
+    pub fn length(&self) -> f32 {
+        *self.distances.last().unwrap()
+    }
+
+    //the point a distance along the path
+    //past the end, exiting paths carry on in a straight line and looping paths start over
+    pub fn at_distance(&self, along: f32) -> (f32, f32) {
+        let length = self.length();
+        if length <= 0.0 {
+            return self.start();
+        }
+        let along = match self.end {
+            PathEnd::Loop => along.rem_euclid(length),
+            PathEnd::Exit => along.max(0.0),
+        };
+        if along >= length {
+            let (dx, dy) = self.end_heading();
+            let (x, y) = *self.samples.last().unwrap();
+            return (x + dx * (along - length), y + dy * (along - length));
+        }
+        //the first sample further along than that
+        let i = self.distances.partition_point(|d| *d <= along).max(1);
+        let (d0, d1) = (self.distances[i - 1], self.distances[i]);
+        let t = if d1 > d0 {
+            (along - d0) / (d1 - d0)
+        } else {
+            0.0
+        };
+        let ((x0, y0), (x1, y1)) = (self.samples[i - 1], self.samples[i]);
+        (x0 + (x1 - x0) * t, y0 + (y1 - y0) * t)
+    }
+
+    //the way the path is going at its end, with a length of 1
+    pub fn end_heading(&self) -> (f32, f32) {
+        let curve = (self.samples.len() - 1) / SAMPLES_PER_CURVE - 1;
+        let (x0, y0) = self.point_on(curve, 0.999);
+        let (x1, y1) = self.point_on(curve, 1.0);
+        let length = distance((x0, y0), (x1, y1));
+        if length > 0.0 {
+            ((x1 - x0) / length, (y1 - y0) / length)
+        } else {
+            (0.0, 0.0)
+        }
+    }
+
+    //a point partway through one of the curves, with t going from 0 at its start to 1 at its end
+    fn point_on(&self, curve: usize, t: f32) -> (f32, f32) {
+        let p = &self.points;
+        match self.curve {
+            Curve::CatmullRom => {
+                //the points before and after the curve shape it. open paths repeat their end points
+                let n = p.len() as isize;
+                let get = |i: isize| match self.end {
+                    PathEnd::Loop => p[i.rem_euclid(n) as usize],
+                    PathEnd::Exit => p[i.clamp(0, n - 1) as usize],
+                };
+                let i = curve as isize;
+                let (p0, p1, p2, p3) = (get(i - 1), get(i), get(i + 1), get(i + 2));
+                let (t2, t3) = (t * t, t * t * t);
+                let blend = |a: f32, b: f32, c: f32, d: f32| {
+                    0.5 * (2.0 * b
+                        + (c - a) * t
+                        + (2.0 * a - 5.0 * b + 4.0 * c - d) * t2
+                        + (3.0 * b - a - 3.0 * c + d) * t3)
+                };
+                (blend(p0.0, p1.0, p2.0, p3.0), blend(p0.1, p1.1, p2.1, p3.1))
+            }
+            Curve::Bezier => {
+                let i = curve * 3;
+                let (p0, p1, p2, p3) = (p[i], p[i + 1], p[i + 2], p[i + 3]);
+                let u = 1.0 - t;
+                let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+                (
+                    a * p0.0 + b * p1.0 + c * p2.0 + d * p3.0,
+                    a * p0.1 + b * p1.1 + c * p2.1 + d * p3.1,
+                )
+            }
+        }
+    }
+}
+
+fn distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+    ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt()
+}
+
+//look up a path by name
+pub fn find_path<'a>(paths: &'a [Spline], name: &str) -> Option<&'a Spline> {
+    paths.iter().find(|p| p.name == name)
+}
@@ -0,0 +1,506 @@
+//stages, loaded from a data file
+//a stage is a timeline of waves. each wave names an enemy archetype, how many of them come in,
+//how they are lined up, where they appear and how they move once they're in
+
+use crate::difficulty::Difficulty;
+use crate::enemy::EnemyDef;
+use crate::patterns::{find_movement, Movement, Pattern};
+use crate::playfield::Playfield;
+use crate::rng::Rng;
+use crate::spline::{find_path, Curve, PathEnd, Spline};
+use crate::state::DESPAWN_MARGIN;
+use serde::Deserialize;
+use std::path::Path;
+use toml::Spanned;
+
+//how far outside the playfield the front of a wave starts when it comes in from an edge
+pub const ENTRY_MARGIN: f32 = 50.0;
+
+//how the enemies in a wave are arranged
+//formations are laid out facing the way the wave travels, so they come in the right way around from any edge
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Formation {
+    //side by side, spacing apart, centered on the entry point
+    #[default]
+    Line,
+    //a leader at the entry point, with the rest trailing back from it in two wings
+    V,
+    //one behind another, with the leader at the entry point
+    Column,
+    //a ring around the entry point, with neighbors spacing apart
+    Circle,
+    //scattered at random along the whole edge the wave comes in from
+    Edge,
+}
+
+impl Formation {
+    //where each of count enemies goes, relative to the entry point
+    //+y is the way the wave travels and +x is to its left, as if it were coming down the screen
+    //edge formations scatter the enemies across a length centered on the entry point
+    pub fn offsets(&self, count: u32, spacing: f32, length: f32, rng: &mut Rng) -> Vec<(f32, f32)> {
+        match self {
+            Formation::Line => {
+                let width = spacing * count.saturating_sub(1) as f32;
+                (0..count)
+                    .map(|i| (spacing * i as f32 - width / 2.0, 0.0))
+                    .collect()
+            }
+            Formation::V => (0..count)
+                .map(|i| {
+                    let rank = i.div_ceil(2) as f32;
+                    let side = if i % 2 == 1 { -1.0 } else { 1.0 };
+                    (side * rank * spacing, -rank * spacing)
+                })
+                .collect(),
+            Formation::Column => (0..count).map(|i| (0.0, -spacing * i as f32)).collect(),
+            Formation::Circle => {
+                if count <= 1 {
+                    return vec![(0.0, 0.0); count as usize];
+                }
+                //the radius that puts neighbors spacing apart
+                let step = std::f32::consts::TAU / count as f32;
+                let radius = spacing / (2.0 * (step / 2.0).sin());
+                (0..count)
+                    .map(|i| {
+                        let (sin, cos) = (step * i as f32).sin_cos();
+                        (radius * sin, radius * cos)
+                    })
+                    .collect()
+            }
+            Formation::Edge => (0..count)
+                .map(|_| (rng.range(-length / 2.0, length / 2.0), 0.0))
+                .collect(),
+        }
+    }
+}
+
+//an edge of the playfield waves can come in from
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl Side {
+    //the way into the playfield from this edge, with a length of 1
+    pub fn heading(&self) -> (f32, f32) {
+        match self {
+            Side::Top => (0.0, 1.0),
+            Side::Bottom => (0.0, -1.0),
+            Side::Left => (1.0, 0.0),
+            Side::Right => (-1.0, 0.0),
+        }
+    }
+
+    //the point on this edge at a distance along it, and how long the edge is
+    //distances are x for the top and bottom, and y for the left and right
+    fn point(&self, playfield: &Playfield, along: f32) -> (f32, f32) {
+        match self {
+            Side::Top => (along, playfield.top),
+            Side::Bottom => (along, playfield.bottom),
+            Side::Left => (playfield.left, along),
+            Side::Right => (playfield.right, along),
+        }
+    }
+
+    fn span(&self, playfield: &Playfield) -> (f32, f32) {
+        match self {
+            Side::Top | Side::Bottom => (playfield.left, playfield.right),
+            Side::Left | Side::Right => (playfield.top, playfield.bottom),
+        }
+    }
+}
+
+//where a wave appears
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Entry {
+    //at a point, facing down the screen
+    Point(f32, f32),
+    //just outside an edge, at a distance along it. None is the middle of the edge
+    Edge(Side, Option<f32>),
+}
+
+//one wave of enemies on the timeline
+#[derive(Clone, Debug)]
+pub struct WaveEvent {
+    //seconds into the stage the wave comes in
+    pub time: f32,
+    //which enemy archetype, by its index in the list
+    pub archetype: usize,
+    pub count: u32,
+    pub formation: Formation,
+    //distance between enemies in the formation, in playfield units
+    pub spacing: f32,
+    //where the formation appears
+    pub entry: Entry,
+    //how each enemy moves. every enemy in the wave gets its own copy
+    pub pattern: Pattern,
+}
+
+impl WaveEvent {
+    //where each of count enemies in the wave appears
+    //waves from an edge start with the front of the formation just outside it
+    pub fn positions(&self, count: u32, playfield: &Playfield, rng: &mut Rng) -> Vec<(f32, f32)> {
+        let (side, at) = match self.entry {
+            Entry::Point(x, y) => {
+                let offsets = self
+                    .formation
+                    .offsets(count, self.spacing, playfield.width(), rng);
+                return offsets
+                    .into_iter()
+                    .map(|(dx, dy)| (x + dx, y + dy))
+                    .collect();
+            }
+            Entry::Edge(side, at) => (side, at),
+        };
+        let (start, end) = side.span(playfield);
+        let middle = (start + end) / 2.0;
+        //edge formations cover the whole edge, so they always start from the middle of it
+        let along = match (self.formation, at) {
+            (Formation::Edge, _) | (_, None) => middle,
+            (_, Some(at)) => at,
+        };
+        let offsets = self
+            .formation
+            .offsets(count, self.spacing, end - start, rng);
+
+        //turn the formation to face the way in, then back it off the edge so its front is just outside
+        let (hx, hy) = side.heading();
+        let front = offsets.iter().map(|o| o.1).fold(f32::MIN, f32::max);
+        let back_off = ENTRY_MARGIN + front;
+        let (edge_x, edge_y) = side.point(playfield, along);
+        let (x, y) = (edge_x - hx * back_off, edge_y - hy * back_off);
+        offsets
+            .into_iter()
+            .map(|(ox, oy)| (x + ox * hy + oy * hx, y - ox * hx + oy * hy))
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Stage {
+    //every wave, in the order they come in
+    pub waves: Vec<WaveEvent>,
+    //the paths waves can follow
+    pub paths: Vec<Spline>,
+    //seconds until the timeline starts over. 0 plays it once
+    pub repeat: f32,
+    //how much harder the stage gets with score and time
+    pub difficulty: Difficulty,
+}
+
+//one [[wave]] table as it is written in the file
+//the name fields keep where they were in the text, so mistakes in them can be pointed out
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WaveEntry {
+    time: Spanned<f32>,
+    enemy: Spanned<String>,
+    #[serde(default = "one")]
+    count: Spanned<u32>,
+    #[serde(default = "line")]
+    formation: Spanned<Formation>,
+    #[serde(default = "default_spacing")]
+    spacing: Spanned<f32>,
+    #[serde(default)]
+    entry: Option<Spanned<(f32, f32)>>,
+    #[serde(default)]
+    from: Option<Spanned<Side>>,
+    #[serde(default)]
+    at: Option<Spanned<f32>>,
+    #[serde(default)]
+    pattern: Option<Spanned<String>>,
+    #[serde(default)]
+    path: Option<Spanned<String>>,
+    #[serde(default = "stopped")]
+    speed: Spanned<f32>,
+    #[serde(default = "default_size")]
+    size: Spanned<f32>,
+    #[serde(default = "default_period")]
+    period: Spanned<f32>,
+}
+
+//one [[path]] table as it is written in the file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PathEntry {
+    name: Spanned<String>,
+    #[serde(default)]
+    curve: Curve,
+    points: Vec<(f32, f32)>,
+    #[serde(default)]
+    end: PathEnd,
+}
+
+fn one() -> Spanned<u32> {
+    Spanned::new(0..0, 1)
+}
+
+fn line() -> Spanned<Formation> {
+    Spanned::new(0..0, Formation::default())
+}
+
+fn default_spacing() -> Spanned<f32> {
+    Spanned::new(0..0, 60.0)
+}
+
+fn stopped() -> Spanned<f32> {
+    Spanned::new(0..0, 0.0)
+}
+
+fn default_size() -> Spanned<f32> {
+    Spanned::new(0..0, 80.0)
+}
+
+fn default_period() -> Spanned<f32> {
+    Spanned::new(0..0, 2.0)
+}
+
+fn flat() -> Spanned<Difficulty> {
+    Spanned::new(0..0, Difficulty::default())
+}
+
+//the layout of a stage file: when to start over, how difficulty builds up,
+//a list of [[path]] tables and a list of [[wave]] tables
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StageFile {
+    #[serde(default)]
+    repeat: f32,
+    #[serde(default = "flat")]
+    difficulty: Spanned<Difficulty>,
+    #[serde(default)]
+    path: Vec<PathEntry>,
+    wave: Vec<Spanned<WaveEntry>>,
+}
+
+//the line a position in the text is on, counting from 1
+fn line_of(text: &str, position: usize) -> usize {
+    text[..position.min(text.len())].matches('\n').count() + 1
+}
+
+//where an optional field is in the text. fields that were left out have an empty span
+fn span_of<T>(field: &Option<Spanned<T>>) -> std::ops::Range<usize> {
+    field.as_ref().map_or(0..0, |f| f.span())
+}
+
+//read a stage from the text of a stage file, matching its waves against the loaded enemy archetypes
+//mistakes are reported with the line they are on
+pub fn parse_stage(text: &str, enemies: &[EnemyDef]) -> Result<Stage, String> {
+    let file: StageFile = match toml::from_str(text) {
+        Ok(f) => f,
+        Err(error) => return Err(error.to_string()),
+    };
+    if file.repeat < 0.0 {
+        return Err("repeat can't be negative".to_string());
+    }
+    if let Some(problem) = file.difficulty.get_ref().problem() {
+        let line = line_of(text, file.difficulty.span().start);
+        return Err(format!("line {}: {}", line, problem));
+    }
+    let mut paths: Vec<Spline> = Vec::new();
+    for entry in file.path {
+        let line = line_of(text, entry.name.span().start);
+        let name = entry.name.get_ref();
+        if find_path(&paths, name).is_some() {
+            return Err(format!(
+                "line {}: there is already a path called \"{}\"",
+                line, name
+            ));
+        }
+        match Spline::new(name, entry.curve, entry.points, entry.end) {
+            Ok(path) => paths.push(path),
+            Err(problem) => return Err(format!("line {}: path \"{}\": {}", line, name, problem)),
+        }
+    }
+    let mut waves = Vec::new();
+    for wave in file.wave {
+        //fields that were left out point at the start of the wave instead
+        let wave_start = wave.span().start;
+        let wave = wave.into_inner();
+        let at = |spanned: std::ops::Range<usize>| {
+            line_of(
+                text,
+                if spanned.end > 0 {
+                    spanned.start
+                } else {
+                    wave_start
+                },
+            )
+        };
+
+        let time = *wave.time.get_ref();
+        let count = *wave.count.get_ref();
+        let formation = *wave.formation.get_ref();
+        let spacing = *wave.spacing.get_ref();
+        let entry = wave.entry.as_ref().map(|e| *e.get_ref());
+        let from = wave.from.as_ref().map(|f| *f.get_ref());
+        let speed = *wave.speed.get_ref();
+        let size = *wave.size.get_ref();
+        let period = *wave.period.get_ref();
+        let problem = if time < 0.0 {
+            Some((at(wave.time.span()), "time can't be negative".to_string()))
+        } else if file.repeat > 0.0 && time >= file.repeat {
+            Some((
+                at(wave.time.span()),
+                "time must be before the stage repeats".to_string(),
+            ))
+        } else if count == 0 {
+            Some((
+                at(wave.count.span()),
+                "count must be at least 1".to_string(),
+            ))
+        } else if count > 1 && spacing <= 0.0 {
+            //otherwise the whole wave would be stacked on top of itself
+            Some((
+                at(wave.spacing.span()),
+                "spacing must be more than 0".to_string(),
+            ))
+        } else if entry.is_some() == from.is_some() {
+            Some((
+                at(span_of(&wave.from)),
+                "a wave needs either an entry point or an edge to come in from, not both"
+                    .to_string(),
+            ))
+        } else if wave.at.is_some() && from.is_none() {
+            Some((at(span_of(&wave.at)), "at only goes with from".to_string()))
+        } else if formation == Formation::Edge && from.is_none() {
+            Some((
+                at(wave.formation.span()),
+                "edge formations need an edge to come in from".to_string(),
+            ))
+        } else if entry.is_some_and(|(x, y)| !Playfield::default().contains(x, y, DESPAWN_MARGIN)) {
+            //anything that far out is removed as soon as it appears
+            Some((
+                at(span_of(&wave.entry)),
+                "the entry point is too far outside the playfield".to_string(),
+            ))
+        } else if wave.pattern.is_some() && wave.path.is_some() {
+            Some((
+                at(span_of(&wave.path)),
+                "a wave follows either a pattern or a path, not both".to_string(),
+            ))
+        } else if wave.path.is_some() && speed <= 0.0 {
+            Some((
+                at(wave.speed.span()),
+                "waves following a path need a speed".to_string(),
+            ))
+        } else if from.is_some() && speed <= 0.0 {
+            Some((
+                at(wave.speed.span()),
+                "waves coming in from an edge need a speed".to_string(),
+            ))
+        } else if size < 0.0 {
+            Some((at(wave.size.span()), "size can't be negative".to_string()))
+        } else if period <= 0.0 {
+            Some((
+                at(wave.period.span()),
+                "period must be more than 0".to_string(),
+            ))
+        } else {
+            None
+        };
+        if let Some((line, problem)) = problem {
+            return Err(format!("line {}: {}", line, problem));
+        }
+
+        let name = wave.enemy.get_ref();
+        let archetype = match enemies.iter().position(|e| &e.name == name) {
+            Some(index) => index,
+            None => {
+                return Err(format!(
+                    "line {}: enemy \"{}\" is not in the enemies file",
+                    at(wave.enemy.span()),
+                    name
+                ))
+            }
+        };
+        //waves from an edge head straight in unless told otherwise. waves at a point stay put
+        let (name, span) = match (&wave.pattern, from) {
+            (Some(pattern), _) => (pattern.get_ref().as_str(), pattern.span()),
+            (None, Some(_)) => ("straight", 0..0),
+            (None, None) => ("still", 0..0),
+        };
+        let (movement, heading) = match find_movement(name) {
+            Some(found) => found,
+            None => {
+                return Err(format!(
+                    "line {}: there is no movement pattern called \"{}\"",
+                    at(span),
+                    name
+                ))
+            }
+        };
+        let entry = match (entry, from) {
+            (Some((x, y)), _) => Entry::Point(x, y),
+            (None, Some(side)) => Entry::Edge(side, wave.at.map(|at| *at.get_ref())),
+            (None, None) => unreachable!("checked above"),
+        };
+        //patterns without a way of their own go the way the wave comes in, or down the screen
+        let heading = heading.unwrap_or(match from {
+            Some(side) => side.heading(),
+            None => (0.0, 1.0),
+        });
+        //waves from an edge start outside the playfield, so they have to move into it
+        if let (Some(side), None) = (from, &wave.path) {
+            let (hx, hy) = heading;
+            let (ix, iy) = side.heading();
+            if movement == Movement::Still || hx * ix + hy * iy <= 0.0 {
+                return Err(format!(
+                    "line {}: pattern \"{}\" doesn't head into the playfield from the {} edge",
+                    at(span),
+                    name,
+                    format!("{:?}", side).to_lowercase()
+                ));
+            }
+        }
+        let mut pattern = match &wave.path {
+            Some(name) => match paths.iter().position(|p| p.name == *name.get_ref()) {
+                Some(path) => Pattern::follow(path, speed),
+                None => {
+                    return Err(format!(
+                        "line {}: there is no path called \"{}\"",
+                        at(name.span()),
+                        name.get_ref()
+                    ))
+                }
+            },
+            None => Pattern::new(movement, heading, speed),
+        };
+        pattern.size = size;
+        pattern.period = period;
+        waves.push(WaveEvent {
+            time,
+            archetype,
+            count,
+            formation,
+            spacing,
+            entry,
+            pattern,
+        });
+    }
+    //waves can be written in any order. ones at the same time keep the order they were written in
+    waves.sort_by(|a, b| a.time.total_cmp(&b.time));
+    Ok(Stage {
+        waves,
+        paths,
+        repeat: file.repeat,
+        difficulty: file.difficulty.into_inner(),
+    })
+}
+
+//load a stage file
+pub fn load_stage(path: &Path, enemies: &[EnemyDef]) -> Result<Stage, String> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(t) => t,
+        Err(error) => return Err(format!("{} could not be read: {:?}", path.display(), error)),
+    };
+    match parse_stage(&text, enemies) {
+        Ok(stage) => Ok(stage),
+        Err(error) => Err(format!("{}: {}", path.display(), error)),
+    }
+}
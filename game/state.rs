@@ -0,0 +1,1155 @@
+use crate::beam::Beam;
+use crate::broadphase::{Grid, CELL_SIZE};
+use crate::collision::{collides, ray_distance, swept_touches, Shape};
+use crate::difficulty::DifficultyScale;
+use crate::enemy::EnemyDef;
+use crate::entity::{Death, DeathCause, Entity, EntityType, PickupKind, ShipDraw, WeaponType};
+use crate::input::Input;
+use crate::model::{find_model, ModelData, ModelId};
+use crate::playfield::Playfield;
+use crate::pool::EntityHandle;
+use crate::pool::Pool;
+use crate::rng::Rng;
+use crate::stage::{Entry, Stage};
+use crate::timestep::FixedTimestep;
+use crate::weapon::{equip_weapon, Aim, WeaponDef, WeaponId, WeaponKind};
+use std::rc::Rc;
+
+//how the player's ship handles. speeds are in playfield units per second
+const PLAYER_TOP_SPEED: f32 = 300.0;
+const PLAYER_ACCELERATION: f32 = 3000.0;
+const PLAYER_DRAG: f32 = 8.0;
+
+//how far outside the playfield an entity can go before it is destroyed
+pub const DESPAWN_MARGIN: f32 = 200.0;
+//enemies coming in from offscreen get twice as long as they need to reach the playfield, plus this many seconds
+//ones that still aren't in by then are heading the wrong way or stuck, and are removed
+const ENTRY_GRACE: f32 = 5.0;
+
+//where the player starts, and comes back to after dying
+const PLAYER_SPAWN_X: f32 = 40.0;
+const PLAYER_SPAWN_Y: f32 = 30.0;
+const PLAYER_HEALTH: i32 = 1;
+//the weapon the player starts with, by its name in the weapons file
+const PLAYER_WEAPON: &str = "player_basic";
+//the model the player's ship uses, by its name in the models folder
+pub const PLAYER_MODEL: &str = "Cube";
+
+//points for destroying an enemy
+const ENEMY_SCORE: i32 = 100;
+
+//pickups use their enemy's pickup model, shrunk down, and drift slowly down the screen
+const PICKUP_SCALE: f32 = 0.5;
+const PICKUP_SPEED: f32 = 60.0;
+
+//seed for the random numbers the game uses, like whether an enemy drops a pickup
+const RNG_SEED: u32 = 490;
+//room for this many entities is set aside up front, so heavy bullet patterns don't make the pool grow mid game
+const ENTITY_CAPACITY: usize = 4096;
+
+//the total state of the game
+#[derive(Clone)]
+pub struct State {
+    pub p1: EntityHandle,                            //the player's entity
+    pub entities: Pool<Entity>,                      //stores entities in game, including the player
+    pub clock: FixedTimestep, //regulates game speed. the game moves forward in fixed ticks
+    pub playfield: Playfield, //the area the player is kept inside of
+    pub models: Vec<ModelData>, //stores models
+    pub weapons: Vec<WeaponDef>, //every weapon definition. WeaponType::Projectile refers to these by index
+    pub score: i32, //stores score: increased by surviving and defeating enemies. higher score increases difficulty
+    pub stage: Stage, //the timeline of enemy waves
+    pub stage_time: f32, //seconds since the stage started, or last started over
+    pub play_time: f32, //seconds since the game started. longer play increases difficulty
+    pub enemies: Vec<EnemyDef>, //every enemy archetype
+    pub rng: Rng,   //random numbers for anything in the game that needs them
+    pub deaths: Vec<Death>, //everything that was removed during the last tick
+    pub contacts: Vec<(EntityHandle, EntityHandle)>, //every pair of entities that touched during the last tick
+    pub beams: Vec<Beam>,                            //every beam fired during the last tick
+    beam_shooters: Vec<EntityHandle>, //entities that fired a beam this tick. the beams are cast after everything moves
+    next_wave: usize,                 //the first wave on the timeline that hasn't come in yet
+    fire_edges: (bool, bool), //fire key presses and releases from frames that haven't been given to a tick yet
+    broadphase: Grid, //finds which entities are close enough to be worth testing for collision
+}
+
+//general functions used by the game state
+impl State {
+    //create the starting game state from a set of loaded models, weapons, enemy archetypes and a stage
+    //there must be at least one archetype to spawn enemies from
+    //fails if the player's model wasn't loaded
+    pub fn new(
+        models: Vec<ModelData>,
+        weapons: Vec<WeaponDef>,
+        enemies: Vec<EnemyDef>,
+        stage: Stage,
+    ) -> Result<State, String> {
+        let player_model = match find_model(&models, PLAYER_MODEL) {
+            Some(id) => id,
+            None => {
+                return Err(format!(
+                    "the player's model \"{}\" was not loaded",
+                    PLAYER_MODEL
+                ))
+            }
+        };
+        let mut entities = Pool::with_capacity(ENTITY_CAPACITY);
+        let p1 = entities.acquire(Entity {
+            etype: EntityType::Player,
+            wtype: equip_weapon(&weapons, PLAYER_WEAPON),
+            weapon_cooldown: 0.0,
+            burst_left: 0,
+            burst_timer: 0.0,
+            ammo_used: 0,
+            reload_timer: 0.0,
+            heat: 0.0,
+            overheat_timer: 0.0,
+            shape: ShipDraw::new(player_model, PLAYER_SPAWN_X, PLAYER_SPAWN_Y),
+            hitbox: models[player_model.0].aabb,
+            health: PLAYER_HEALTH,
+            speed_x: 0.0,
+            speed_y: 0.0,
+            top_speed: PLAYER_TOP_SPEED,
+            acceleration: PLAYER_ACCELERATION,
+            drag: PLAYER_DRAG,
+            collision_damage: 1,
+            is_tangible: true,
+            dead: None,
+            killer: None,
+            hits: Vec::new(),
+            age: 0.0,
+            emitter: None,
+            speed_curve: None,
+            heading: (0.0, 0.0),
+            turn_rate: 0.0,
+            target: None,
+            lifetime: None,
+            beam_damage: 0.0,
+            charge: 0.0,
+            weapon_level: 0,
+            pickup: None,
+            archetype: None,
+            pattern: None,
+            entered: true,
+            entry_time: 0.0,
+        });
+        Ok(State {
+            p1,
+            entities,
+            clock: FixedTimestep::default(),
+            playfield: Playfield::default(),
+            stage,
+            stage_time: 0.0,
+            play_time: 0.0,
+            next_wave: 0,
+            enemies,
+            rng: Rng::new(RNG_SEED),
+            models,
+            weapons,
+            score: 0,
+            deaths: Vec::new(),
+            contacts: Vec::new(),
+            beams: Vec::new(),
+            beam_shooters: Vec::new(),
+            fire_edges: (false, false),
+            broadphase: Grid::new(&Playfield::default(), DESPAWN_MARGIN, CELL_SIZE),
+        })
+    }
+
+    //a loaded model, by its id
+    pub fn model(&self, id: ModelId) -> &ModelData {
+        &self.models[id.0]
+    }
+
+    //the player's entity
+    pub fn player(&self) -> &Entity {
+        match self.entities.get(self.p1) {
+            Some(e) => e,
+            None => panic!("the player entity is missing"),
+        }
+    }
+
+    pub fn player_mut(&mut self) -> &mut Entity {
+        match self.entities.get_mut(self.p1) {
+            Some(e) => e,
+            None => panic!("the player entity is missing"),
+        }
+    }
+
+    //the definition of the weapon an entity has equipped, if it has one
+    pub fn weapon(&self, handle: EntityHandle) -> Option<&WeaponDef> {
+        let id = self.entities.get(handle)?.wtype.id()?;
+        self.weapons.get(id.0)
+    }
+
+    //spawn one shot's worth of projectiles from the entity a handle refers to, using its weapon
+    //the projectiles are fanned out evenly over the weapon's spread, centered on straight ahead
+    //returns the projectiles' handles. empty if the shooter is gone or unarmed
+    pub fn bullet(&mut self, shooter: EntityHandle) -> Vec<EntityHandle> {
+        match self.entities.get(shooter).map(|e| e.wtype) {
+            Some(WeaponType::Projectile(id)) => self.shoot(shooter, id),
+            _ => Vec::new(),
+        }
+    }
+
+    //spawn one shot's worth of projectiles from an entity, using any projectile weapon, held or not
+    pub fn shoot(&mut self, shooter: EntityHandle, weapon: WeaponId) -> Vec<EntityHandle> {
+        let mut shots = Vec::new();
+        let e = match self.entities.get(shooter) {
+            Some(e) => e,
+            None => return shots,
+        };
+        let def = match self.weapons.get(weapon.0) {
+            Some(def) if def.kind == WeaponKind::Projectile => def,
+            _ => return shots,
+        };
+        let angles = def.angles(&def.at_level(e.weapon_level));
+        let (model, damage, punch_through) = (def.model_id, def.damage, def.punch_through);
+        let (size, turn_rate, lifetime) = (def.size, def.turn_rate, def.lifetime);
+        let mut speed = def.speed;
+        //enemy shots get faster as the game gets harder
+        if e.etype == EntityType::Enemy {
+            speed *= self.difficulty().projectile_speed;
+        }
+        let (center_x, center_y) = (e.shape.center_x, e.shape.center_y);
+        let etype = projectile_type(e.etype);
+        let (aim_x, aim_y) = self.aim(e, def.aim);
+
+        for angle in angles {
+            //turn the aim by the angle. y points down the screen, so positive angles turn clockwise
+            let (sin, cos) = angle.sin_cos();
+            let (dir_x, dir_y) = (aim_x * cos - aim_y * sin, aim_x * sin + aim_y * cos);
+            let handle = self.spawn_projectile(
+                etype,
+                model,
+                (center_x, center_y),
+                (dir_x * speed, dir_y * speed),
+                damage,
+                punch_through,
+            );
+            if let Some(projectile) = self.entities.get_mut(handle) {
+                projectile.shape.scale = size;
+                projectile.hitbox = projectile.hitbox.scaled(size);
+                projectile.turn_rate = turn_rate.to_radians();
+                if lifetime > 0.0 {
+                    projectile.lifetime = Some(lifetime);
+                }
+            }
+            shots.push(handle);
+        }
+        shots
+    }
+
+    //the direction an entity's weapon points before any spread is applied, with a length of 1
+    //the player shoots up the screen, everything else shoots down it, unless the weapon aims at the player
+    fn aim(&self, shooter: &Entity, aim: Aim) -> (f32, f32) {
+        let facing = if shooter.etype == EntityType::Player {
+            (0.0, -1.0)
+        } else {
+            (0.0, 1.0)
+        };
+        if aim != Aim::Player {
+            return facing;
+        }
+        let target = self.player();
+        let (dx, dy) = (
+            target.shape.center_x - shooter.shape.center_x,
+            target.shape.center_y - shooter.shape.center_y,
+        );
+        let length = (dx * dx + dy * dy).sqrt();
+        //right on top of the player there's nothing to aim at, so fire straight ahead
+        if length > 0.0 {
+            (dx / length, dy / length)
+        } else {
+            facing
+        }
+    }
+
+    //create a single projectile, moving with a given speed
+    //the shooter's weapon or emitter decides everything about it
+    fn spawn_projectile(
+        &mut self,
+        etype: EntityType,
+        model: ModelId,
+        (center_x, center_y): (f32, f32),
+        (speed_x, speed_y): (f32, f32),
+        damage: i32,
+        punch_through: i32,
+    ) -> EntityHandle {
+        let shape = ShipDraw::new(model, center_x, center_y);
+        let hitbox = self.model(model).circle;
+        let speed = (speed_x * speed_x + speed_y * speed_y).sqrt();
+        let handle = self.create_entity(
+            etype,
+            WeaponType::None,
+            punch_through,
+            shape,
+            hitbox,
+            speed_x,
+            speed_y,
+            speed,
+        );
+        if let Some(projectile) = self.entities.get_mut(handle) {
+            projectile.collision_damage = damage;
+        }
+        handle
+    }
+
+    //the living entity of a type closest to a point
+    pub fn nearest(&self, etype: EntityType, x: f32, y: f32) -> Option<EntityHandle> {
+        let mut best = None;
+        let mut best_distance = f32::MAX;
+        for (h, e) in self.entities.iter() {
+            if e.etype != etype || !e.is_alive() {
+                continue;
+            }
+            let (dx, dy) = (e.shape.center_x - x, e.shape.center_y - y);
+            let distance = dx * dx + dy * dy;
+            if distance < best_distance {
+                best = Some(h);
+                best_distance = distance;
+            }
+        }
+        best
+    }
+
+    //every living entity whose center is within radius of a point
+    pub fn in_radius(&self, x: f32, y: f32, radius: f32) -> Vec<EntityHandle> {
+        self.entities
+            .iter()
+            .filter(|(_, e)| {
+                let (dx, dy) = (e.shape.center_x - x, e.shape.center_y - y);
+                e.is_alive() && dx * dx + dy * dy <= radius * radius
+            })
+            .map(|(h, _)| h)
+            .collect()
+    }
+
+    //turn every homing projectile toward its target for one tick
+    //player projectiles chase the nearest enemy and enemy projectiles chase the player
+    //a projectile whose target is gone picks the nearest one left
+    fn steer_homing(&mut self, dt: f32) {
+        let homing: Vec<EntityHandle> = self
+            .entities
+            .iter()
+            .filter(|(_, e)| e.turn_rate > 0.0 && e.is_alive())
+            .map(|(h, _)| h)
+            .collect();
+        for handle in homing {
+            let (etype, x, y, target) = match self.entities.get(handle) {
+                Some(e) => (e.etype, e.shape.center_x, e.shape.center_y, e.target),
+                None => continue,
+            };
+            let wanted = match etype {
+                EntityType::PlayerProjectile => EntityType::Enemy,
+                EntityType::EnemyProjectile => EntityType::Player,
+                _ => continue,
+            };
+            let target = match target.and_then(|t| self.entities.get(t)) {
+                Some(t) if t.is_alive() => target,
+                _ => self.nearest(wanted, x, y),
+            };
+            let point = target
+                .and_then(|t| self.entities.get(t))
+                .map(|t| (t.shape.center_x, t.shape.center_y));
+            if let Some(e) = self.entities.get_mut(handle) {
+                e.target = target;
+                if let Some((tx, ty)) = point {
+                    e.steer_toward(tx, ty, e.turn_rate * dt);
+                }
+            }
+        }
+    }
+
+    //run every entity's emitter for one tick, spawning whatever projectiles they fire
+    //emitters fire from wherever their entity is. dead entities' emitters stop,
+    //and ones on enemies still flying in from offscreen wait until they're in
+    fn run_emitters(&mut self, dt: f32) {
+        let mut volleys = Vec::new();
+        for e in self.entities.values_mut() {
+            if !e.is_alive() || !e.entered {
+                continue;
+            }
+            if let Some(emitter) = &mut e.emitter {
+                let angles = emitter.update(dt);
+                if !angles.is_empty() {
+                    volleys.push((
+                        projectile_type(e.etype),
+                        (e.shape.center_x, e.shape.center_y),
+                        (emitter.model, emitter.damage, emitter.punch_through),
+                        Rc::clone(&emitter.speed),
+                        angles,
+                    ));
+                }
+            }
+        }
+
+        for (etype, center, (model, damage, punch_through), curve, angles) in volleys {
+            let speed = curve.speed_at(0.0);
+            for angle in angles {
+                //angles are clockwise from straight down the screen
+                let heading = (-angle.sin(), angle.cos());
+                let handle = self.spawn_projectile(
+                    etype,
+                    model,
+                    center,
+                    (heading.0 * speed, heading.1 * speed),
+                    damage,
+                    punch_through,
+                );
+                if let Some(projectile) = self.entities.get_mut(handle) {
+                    projectile.speed_curve = Some(Rc::clone(&curve));
+                    projectile.heading = heading;
+                }
+            }
+        }
+    }
+
+    //how hard the game is right now, from the stage's difficulty curve
+    pub fn difficulty(&self) -> DifficultyScale {
+        self.stage.difficulty.scale(self.score, self.play_time)
+    }
+
+    //run the stage timeline forward, sending in every wave whose time has come
+    //once the timeline is over it starts again, if the stage repeats
+    //the timeline runs faster as difficulty goes up, so waves come in closer together
+    pub fn spawn_cycle(&mut self, delta: f32) {
+        self.stage_time += delta / self.difficulty().spawn_interval;
+        loop {
+            if let Some(wave) = self.stage.waves.get(self.next_wave) {
+                if wave.time > self.stage_time {
+                    break;
+                }
+                self.spawn_wave(self.next_wave);
+                self.next_wave += 1;
+                continue;
+            }
+            //every wave is in. start over once it's time to
+            if self.stage.repeat > 0.0 && self.stage_time >= self.stage.repeat {
+                self.stage_time -= self.stage.repeat;
+                self.next_wave = 0;
+            } else {
+                break;
+            }
+        }
+    }
+
+    //send in a wave of enemies, lined up in its formation at its entry point or just outside its edge
+    //harder waves have more enemies in them
+    //the wave is picked by its index in the stage. returns the enemies' handles
+    pub fn spawn_wave(&mut self, index: usize) -> Vec<EntityHandle> {
+        let wave = &self.stage.waves[index];
+        let count = self.difficulty().wave_count(wave.count);
+        let positions = wave.positions(count, &self.playfield, &mut self.rng);
+        let offscreen = matches!(wave.entry, Entry::Edge(..));
+        let (archetype, pattern) = (wave.archetype, wave.pattern.clone());
+        let mut handles = Vec::new();
+        for (x, y) in positions {
+            let handle = self.spawn_archetype(archetype, x, y);
+            let distance = self.playfield.distance_outside(x, y);
+            if let Some(e) = self.entities.get_mut(handle) {
+                e.pattern = Some(pattern.clone());
+                e.entered = !offscreen;
+                e.entry_time = ENTRY_GRACE + 2.0 * distance / pattern.speed.max(1.0);
+            }
+            handles.push(handle);
+        }
+        handles
+    }
+
+    //spawn an enemy of an archetype, by its index in the list
+    //its weapon starts out cooling down, so it doesn't fire the instant it appears
+    //enemies are tougher the harder the game is
+    pub fn spawn_archetype(
+        &mut self,
+        archetype: usize,
+        center_x: f32,
+        center_y: f32,
+    ) -> EntityHandle {
+        let def = &self.enemies[archetype];
+        let shape = ShipDraw::new(def.model_id, center_x, center_y);
+        let health = self.difficulty().enemy_health(def.health);
+        let (wtype, hitbox) = (def.wtype, self.model(def.model_id).aabb);
+        //create a new entity and add it to the game state's entity list
+        let handle = self.create_entity(
+            EntityType::Enemy,
+            wtype,
+            health,
+            shape,
+            hitbox,
+            0.0,
+            0.0,
+            0.0,
+        );
+        let cooldown = match self.weapon(handle) {
+            Some(def) => def.cooldown,
+            None => 0.0,
+        };
+        if let Some(e) = self.entities.get_mut(handle) {
+            e.weapon_cooldown = cooldown;
+            e.archetype = Some(archetype);
+        }
+        handle
+    }
+
+    //spawn a pickup that drifts down the screen until the player takes it
+    pub fn spawn_pickup(
+        &mut self,
+        kind: PickupKind,
+        model: ModelId,
+        center_x: f32,
+        center_y: f32,
+    ) -> EntityHandle {
+        let mut shape = ShipDraw::new(model, center_x, center_y);
+        shape.scale = PICKUP_SCALE;
+        let hitbox = self.model(model).aabb.scaled(PICKUP_SCALE);
+        let handle = self.create_entity(
+            EntityType::Pickup,
+            WeaponType::None,
+            1,
+            shape,
+            hitbox,
+            0.0,
+            PICKUP_SPEED,
+            PICKUP_SPEED,
+        );
+        if let Some(e) = self.entities.get_mut(handle) {
+            e.pickup = Some(kind);
+            e.collision_damage = 0;
+        }
+        handle
+    }
+
+    //give a pickup to whatever touched it, and use the pickup up
+    //upgrades stop at the weapon's top level. a new weapon keeps the level the old one had
+    fn collect(&mut self, pickup: EntityHandle, taker: EntityHandle) {
+        let kind = match self.entities.get_mut(pickup) {
+            Some(e) => {
+                e.kill(DeathCause::Collected);
+                e.killer = Some(taker);
+                e.pickup
+            }
+            None => None,
+        };
+        let max_level = match kind {
+            Some(PickupKind::Upgrade) => self.weapon(taker).map(|def| def.max_level),
+            Some(PickupKind::Weapon(id)) => self.weapons.get(id.0).map(|def| def.max_level),
+            None => None,
+        };
+        let weapons = &self.weapons;
+        if let Some(e) = self.entities.get_mut(taker) {
+            match kind {
+                Some(PickupKind::Upgrade) => e.weapon_level += 1,
+                Some(PickupKind::Weapon(id)) => {
+                    e.wtype = weapons[id.0].equip(id);
+                    e.reset_weapon();
+                }
+                None => {}
+            }
+            e.weapon_level = e.weapon_level.min(max_level.unwrap_or(0));
+        }
+    }
+
+    //entity creation. the spawning functions will call this to actually generate the entity
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_entity(
+        &mut self,
+        entype: EntityType,
+        weptype: WeaponType,
+        health: i32,
+        enshape: ShipDraw,
+        hitbox: Shape,
+        xspeed: f32,
+        yspeed: f32,
+        topspeed: f32,
+    ) -> EntityHandle {
+        self.entities.acquire(Entity {
+            etype: entype,
+            wtype: weptype,
+            weapon_cooldown: 0.0,
+            burst_left: 0,
+            burst_timer: 0.0,
+            ammo_used: 0,
+            reload_timer: 0.0,
+            heat: 0.0,
+            overheat_timer: 0.0,
+            shape: enshape,
+            hitbox,
+            health,
+            speed_x: xspeed,
+            speed_y: yspeed,
+            top_speed: topspeed,
+            acceleration: 0.0,
+            drag: 0.0,
+            collision_damage: 1,
+            is_tangible: true,
+            dead: None,
+            killer: None,
+            hits: Vec::new(),
+            age: 0.0,
+            emitter: None,
+            speed_curve: None,
+            heading: (0.0, 0.0),
+            turn_rate: 0.0,
+            target: None,
+            lifetime: None,
+            beam_damage: 0.0,
+            charge: 0.0,
+            weapon_level: 0,
+            pickup: None,
+            archetype: None,
+            pattern: None,
+            entered: true,
+            entry_time: 0.0,
+        })
+    }
+
+    //remove an entity after it meets its conditions to be removed
+    //ex: a projectile leaves the screen, an object loses its health, etc.
+    //the entity is only marked here. it is taken out of the list at the end of the tick
+    //returns false if the handle no longer refers to anything
+    pub fn despawn(&mut self, handle: EntityHandle) -> bool {
+        match self.entities.get_mut(handle) {
+            Some(e) => {
+                e.kill(DeathCause::Despawned);
+                true
+            }
+            None => false,
+        }
+    }
+
+    //take every dead entity out of the entity list, recording why each one died
+    //the player is never removed. it goes back to where it started instead
+    fn reap(&mut self) {
+        let dead: Vec<EntityHandle> = self
+            .entities
+            .iter()
+            .filter(|(_, e)| !e.is_alive())
+            .map(|(h, _)| h)
+            .collect();
+        for handle in dead {
+            let e = match self.entities.get(handle) {
+                Some(e) => e,
+                None => continue,
+            };
+            let death = Death {
+                handle,
+                etype: e.etype,
+                cause: e.dead.unwrap_or(DeathCause::Despawned),
+                killer: e.killer,
+                center_x: e.shape.center_x,
+                center_y: e.shape.center_y,
+            };
+            //destroyed enemies might leave a pickup behind, depending on their archetype
+            let archetype = e.archetype.and_then(|a| self.enemies.get(a));
+            if let Some(def) = archetype {
+                if death.cause == DeathCause::Destroyed
+                    && !def.pickups.is_empty()
+                    && self.rng.chance(def.drop_chance)
+                {
+                    let kind = def.pickups[self.rng.below(def.pickups.len())];
+                    let model = def.pickup_model_id;
+                    self.spawn_pickup(kind, model, death.center_x, death.center_y);
+                }
+            }
+            if handle == self.p1 {
+                self.respawn_player();
+            } else {
+                self.entities.release(handle);
+            }
+            self.deaths.push(death);
+        }
+
+        //award points for what was destroyed
+        for death in &self.deaths {
+            if death.etype == EntityType::Enemy && death.cause == DeathCause::Destroyed {
+                self.score += ENEMY_SCORE;
+            }
+        }
+    }
+
+    //find every pair of living, tangible entities that are allowed to hit each other and are touching,
+    //and store them in contacts
+    //entities are bucketed into a grid first, so only entities near each other are tested
+    //projectiles are tested along the whole path they moved this tick, so fast ones can't skip past things
+    fn find_contacts(&mut self) {
+        self.contacts.clear();
+        if *self.broadphase.playfield() != self.playfield {
+            self.broadphase = Grid::new(&self.playfield, DESPAWN_MARGIN, CELL_SIZE);
+        }
+
+        let mut handles = Vec::new();
+        let mut types = Vec::new();
+        let mut shapes = Vec::new();
+        let mut motions = Vec::new();
+        //what the grid sorts by. for projectiles this covers their whole path
+        let mut areas = Vec::new();
+        for (h, e) in self.entities.iter() {
+            if e.is_alive() && e.is_tangible {
+                handles.push(h);
+                types.push(e.etype);
+                shapes.push(e.bounds());
+                motions.push(e.motion());
+                areas.push(if e.etype.is_projectile() {
+                    e.swept_bounds()
+                } else {
+                    e.bounds()
+                });
+            }
+        }
+
+        let mut pairs = Vec::new();
+        self.broadphase.pairs_with(&areas, &mut pairs, |a, b| {
+            if !collides(types[a], types[b]) {
+                return false;
+            }
+            if types[a].is_projectile() || types[b].is_projectile() {
+                //move a relative to b, as if b stood still
+                let dx = motions[a].0 - motions[b].0;
+                let dy = motions[a].1 - motions[b].1;
+                swept_touches(&shapes[a], dx, dy, &shapes[b])
+            } else {
+                shapes[a].touches(&shapes[b])
+            }
+        });
+        //keep the same order as the entity list so the results don't depend on the grid
+        pairs.sort_unstable();
+        for (a, b) in pairs {
+            self.contacts.push((handles[a], handles[b]));
+        }
+    }
+
+    //apply damage for every contact found this tick
+    //something that died from an earlier contact this tick can't hit anything else,
+    //and a projectile only ever hits the same thing once
+    fn resolve_contacts(&mut self) {
+        for i in 0..self.contacts.len() {
+            let (a, b) = self.contacts[i];
+            let (a_damage, b_damage) = match (self.entities.get(a), self.entities.get(b)) {
+                (Some(a_entity), Some(b_entity)) => {
+                    if !a_entity.is_alive()
+                        || !b_entity.is_alive()
+                        || a_entity.hits.contains(&b)
+                        || b_entity.hits.contains(&a)
+                    {
+                        continue;
+                    }
+                    //pickups don't hurt, they get taken
+                    if a_entity.etype == EntityType::Pickup {
+                        self.collect(a, b);
+                        continue;
+                    }
+                    if b_entity.etype == EntityType::Pickup {
+                        self.collect(b, a);
+                        continue;
+                    }
+                    (a_entity.collision_damage, b_entity.collision_damage)
+                }
+                _ => continue,
+            };
+            if let Some(e) = self.entities.get_mut(a) {
+                e.touch(b_damage, b);
+            }
+            if let Some(e) = self.entities.get_mut(b) {
+                e.touch(a_damage, a);
+            }
+        }
+    }
+
+    //put the player back where they started, at full health
+    //dying costs half of the weapon's upgrade levels, rounded up
+    fn respawn_player(&mut self) {
+        let p1 = self.player_mut();
+        p1.weapon_level /= 2;
+        p1.shape = ShipDraw::new(p1.shape.model, PLAYER_SPAWN_X, PLAYER_SPAWN_Y);
+        p1.speed_x = 0.0;
+        p1.speed_y = 0.0;
+        p1.health = PLAYER_HEALTH;
+        p1.dead = None;
+        p1.killer = None;
+    }
+
+    //fire an entity's weapon, if it has cooled down since the last shot and isn't still firing a burst
+    //the weapon's cooldown is added to the entity's cooldown each shot, so holding fire
+    //gives a steady rate no matter how many ticks there are per second
+    //weapons with ammo or heat also can't fire while reloading or overheated, and use some up each pull
+    //returns true if the weapon fired
+    pub fn fire_weapon(&mut self, shooter: EntityHandle) -> bool {
+        let dt = self.clock.dt();
+        let fire_rate = self.difficulty().fire_rate;
+        //the weapon and the shooter are borrowed separately, so nothing is copied on ticks it doesn't fire
+        let e = match self.entities.get_mut(shooter) {
+            Some(e) if !e.weapon_locked() => e,
+            _ => return false,
+        };
+        let def = match e.wtype.id().and_then(|id| self.weapons.get(id.0)) {
+            Some(def) => def,
+            None => return false,
+        };
+        //beams don't cool down. they fire every tick the key is held, heating up as they go
+        if def.kind == WeaponKind::Beam {
+            e.spend_shot(def, def.heat_per_shot * dt);
+            self.beam_shooters.push(shooter);
+            return true;
+        }
+        if e.weapon_cooldown > 0.0 || e.burst_left > 0 {
+            return false;
+        }
+        let mut cooldown = def.at_level(e.weapon_level).cooldown;
+        //enemies fire more often as the game gets harder
+        if e.etype == EntityType::Enemy {
+            cooldown /= fire_rate;
+        }
+        e.spend_shot(def, def.heat_per_shot);
+        let (burst, interval) = (def.burst, def.burst_interval);
+        self.bullet(shooter);
+        if let Some(e) = self.entities.get_mut(shooter) {
+            e.weapon_cooldown += cooldown;
+            //the rest of the burst is fired by fire_bursts over the next ticks
+            e.burst_left = burst.saturating_sub(1);
+            e.burst_timer = interval;
+        }
+        true
+    }
+
+    //fire a charged shot from a weapon that has been charging up
+    //it uses up the equipped weapon's ammo and heat like any other shot,
+    //and the weapon waits out the charged weapon's cooldown before firing again
+    //returns false if the weapon is reloading or overheated
+    fn fire_charged(&mut self, shooter: EntityHandle, weapon: WeaponId) -> bool {
+        let cooldown = self.weapons[weapon.0].cooldown;
+        let e = match self.entities.get_mut(shooter) {
+            Some(e) if !e.weapon_locked() => e,
+            _ => return false,
+        };
+        match e.wtype.id().and_then(|id| self.weapons.get(id.0)) {
+            Some(def) => e.spend_shot(def, def.heat_per_shot),
+            None => return false,
+        }
+        self.shoot(shooter, weapon);
+        if let Some(e) = self.entities.get_mut(shooter) {
+            e.weapon_cooldown = e.weapon_cooldown.max(cooldown);
+        }
+        true
+    }
+
+    //cast the beam of everything that fired one this tick, damaging whatever each one stops at
+    //a beam stops at the first living, tangible thing in its way that its shooter's projectiles could hit
+    //damage builds up on the shooter each tick, and is dealt a whole point at a time
+    fn cast_beams(&mut self, dt: f32) {
+        for shooter in std::mem::take(&mut self.beam_shooters) {
+            let (model, aim, direction, range, width, dps) = match self.weapon(shooter) {
+                Some(def) => (
+                    def.model_id,
+                    def.aim,
+                    def.direction,
+                    def.range,
+                    def.width,
+                    def.dps,
+                ),
+                None => continue,
+            };
+            let e = match self.entities.get(shooter) {
+                Some(e) if e.is_alive() => e,
+                _ => continue,
+            };
+            let (x, y) = (e.shape.center_x, e.shape.center_y);
+            let etype = projectile_type(e.etype);
+            let (aim_x, aim_y) = self.aim(e, aim);
+            //turn the aim by the weapon's direction, the same way shots are turned
+            let (sin, cos) = direction.to_radians().sin_cos();
+            let dir = (aim_x * cos - aim_y * sin, aim_x * sin + aim_y * cos);
+
+            let mut length = range;
+            let mut hit = None;
+            for (h, other) in self.entities.iter() {
+                if h == shooter
+                    || !other.is_alive()
+                    || !other.is_tangible
+                    || !collides(etype, other.etype)
+                {
+                    continue;
+                }
+                if let Some(distance) =
+                    ray_distance((x, y), dir, length, width / 2.0, &other.bounds())
+                {
+                    if distance < length || hit.is_none() {
+                        length = distance;
+                        hit = Some(h);
+                    }
+                }
+            }
+
+            if let Some(target) = hit {
+                let damage = match self.entities.get_mut(shooter) {
+                    Some(e) => {
+                        e.beam_damage += dps * dt;
+                        let whole = e.beam_damage.floor();
+                        e.beam_damage -= whole;
+                        whole as i32
+                    }
+                    None => 0,
+                };
+                if damage > 0 {
+                    if let Some(t) = self.entities.get_mut(target) {
+                        t.damage(damage, Some(shooter));
+                    }
+                }
+            }
+            self.beams.push(Beam {
+                shooter,
+                model,
+                x,
+                y,
+                dir_x: dir.0,
+                dir_y: dir.1,
+                length,
+                width,
+                hit,
+            });
+        }
+    }
+
+    //fire the next shot of every burst that is due
+    fn fire_bursts(&mut self) {
+        let due: Vec<EntityHandle> = self
+            .entities
+            .iter()
+            .filter(|(_, e)| e.burst_left > 0 && e.burst_timer <= 0.0)
+            .map(|(h, _)| h)
+            .collect();
+        for handle in due {
+            let interval = match self.weapon(handle) {
+                Some(def) => def.burst_interval,
+                None => 0.0,
+            };
+            //short intervals can have more than one shot due in a single tick
+            loop {
+                match self.entities.get_mut(handle) {
+                    Some(e) if e.burst_left > 0 && e.burst_timer <= 0.0 => {
+                        e.burst_left -= 1;
+                        e.burst_timer += interval;
+                    }
+                    _ => break,
+                }
+                self.bullet(handle);
+            }
+        }
+    }
+
+    //change how many ticks per second the game runs at
+    pub fn set_tick_rate(&mut self, tick_rate: f32) {
+        self.clock = FixedTimestep::new(tick_rate);
+    }
+
+    //advance the game by however many ticks fit in the time since the last frame
+    //returns how many ticks were run
+    //key presses and releases only go to the first tick. if the frame was too short for a tick,
+    //they are saved for the next frame's, so none are lost
+    pub fn update(&mut self, input: &Input, frame_delta: f32) -> u32 {
+        self.fire_edges.0 |= input.fire_pressed;
+        self.fire_edges.1 |= input.fire_released;
+        let ticks = self.clock.advance(frame_delta);
+        for _ in 0..ticks {
+            let mut tick_input = *input;
+            (tick_input.fire_pressed, tick_input.fire_released) = self.fire_edges;
+            self.fire_edges = (false, false);
+            self.tick(&tick_input);
+        }
+        ticks
+    }
+
+    //the charge level of the player's shot, for the HUD. 0 if it isn't charged up enough to fire yet
+    pub fn charge_level(&self) -> u32 {
+        match self.weapon(self.p1) {
+            Some(def) => def.charge_level(self.player().charge),
+            None => 0,
+        }
+    }
+
+    //how far the player's shot is toward being fully charged, from 0 to 1, for the HUD
+    pub fn charge_progress(&self) -> f32 {
+        match self.weapon(self.p1) {
+            Some(def) if def.can_charge() => {
+                let full = def.charge_time * def.charged_ids.len() as f32;
+                (self.player().charge / full).min(1.0)
+            }
+            _ => 0.0,
+        }
+    }
+
+    //rounds left in the player's weapon and how many it holds, for the HUD. None if it never runs out
+    pub fn ammo(&self) -> Option<(u32, u32)> {
+        match self.weapon(self.p1) {
+            Some(def) if def.ammo > 0 => {
+                let used = self.player().ammo_used.min(def.ammo);
+                Some((def.ammo - used, def.ammo))
+            }
+            _ => None,
+        }
+    }
+
+    //how hot the player's weapon is, from 0 to 1, for the HUD. None if it never heats up
+    pub fn heat(&self) -> Option<f32> {
+        match self.weapon(self.p1) {
+            Some(def) if def.heat_per_shot > 0.0 => Some(self.player().heat),
+            _ => None,
+        }
+    }
+
+    //true while the player's weapon is reloading, and while it is cooling off after overheating
+    pub fn reloading(&self) -> bool {
+        self.player().reload_timer > 0.0
+    }
+
+    pub fn overheated(&self) -> bool {
+        self.player().overheat_timer > 0.0
+    }
+
+    //fire the player's weapon from the fire key
+    //weapons that charge fire once when the key goes down, build up charge while it is held,
+    //and fire the charged weapon for the level reached when it comes up
+    //anything else just fires whenever it can while the key is held
+    fn pull_trigger(&mut self, input: &Input, dt: f32) {
+        let charged = match self.weapon(self.p1) {
+            Some(def) if def.can_charge() => {
+                let level = def.charge_level(self.player().charge);
+                Some(match level {
+                    0 => None,
+                    _ => Some(def.charged_ids[level as usize - 1]),
+                })
+            }
+            _ => None,
+        };
+        let charged = match charged {
+            Some(charged) => charged,
+            None => {
+                if input.fire || input.fire_pressed {
+                    self.fire_weapon(self.p1);
+                }
+                return;
+            }
+        };
+
+        if input.fire_pressed {
+            self.fire_weapon(self.p1);
+        }
+        //let go, whether or not the release was seen
+        if input.fire_released || !input.fire {
+            self.player_mut().charge = 0.0;
+            if let Some(weapon) = charged {
+                self.fire_charged(self.p1, weapon);
+            }
+        }
+        if input.fire {
+            self.player_mut().charge += dt;
+        }
+    }
+
+    //how far the current frame is between the last tick and the next one. pass this to the draw code
+    pub fn alpha(&self) -> f32 {
+        self.clock.alpha()
+    }
+
+    //advance the game by exactly one tick
+    pub fn tick(&mut self, input: &Input) {
+        let dt = self.clock.dt();
+
+        self.deaths.clear();
+        self.beams.clear();
+
+        //remember where everything was so drawing can blend between ticks
+        for e in self.entities.values_mut() {
+            e.shape.save_position();
+        }
+
+        //let weapons cool down and carry on any bursts, then fire the player's if the key is held
+        let weapons = &self.weapons;
+        for e in self.entities.values_mut() {
+            e.cool_down(dt);
+            if let Some(def) = e.wtype.id().and_then(|id| weapons.get(id.0)) {
+                e.cool_weapon(def, dt);
+            }
+        }
+        self.fire_bursts();
+        self.pull_trigger(input, dt);
+
+        //enemies fire whenever their weapons are ready. each one keeps its own cooldown
+        //ones still flying in from offscreen hold their fire until they can be seen
+        let enemies: Vec<EntityHandle> = self
+            .entities
+            .iter()
+            .filter(|(_, e)| e.etype == EntityType::Enemy && e.is_alive() && e.entered)
+            .map(|(h, _)| h)
+            .collect();
+        for enemy in enemies {
+            self.fire_weapon(enemy);
+        }
+        self.run_emitters(dt);
+        self.steer_homing(dt);
+
+        //move the player, then keep them inside the playfield
+        let (dir_x, dir_y) = input.direction();
+        let playfield = self.playfield;
+        let p1 = self.player_mut();
+        p1.thrust(dir_x, dir_y, dt);
+        p1.integrate(dt);
+        p1.clamp_to(&playfield);
+
+        //move non player entities
+        let player = self.p1;
+        let paths = &self.stage.paths;
+        for (handle, e) in self.entities.iter_mut() {
+            e.age += dt;
+            if handle != player {
+                //patterns go by the age at the start of the tick
+                if let Some(mut pattern) = e.pattern.take() {
+                    pattern.update(e, paths, e.age - dt, dt);
+                    e.pattern = Some(pattern);
+                }
+                e.follow_speed_curve();
+                e.integrate(dt);
+            }
+        }
+
+        //beams hit from where their shooters ended up
+        self.cast_beams(dt);
+
+        //check collison
+        //this happens before the bounds check, so something leaving the playfield
+        //can still hit things on its way out
+        self.find_contacts();
+        self.resolve_contacts();
+
+        //destroy any entities that are out of bounds or have lived out their lifetime
+        //enemies still coming in from offscreen are left alone until they reach the playfield,
+        //as long as they get there in time
+        for (handle, e) in self.entities.iter_mut() {
+            let (x, y) = (e.shape.center_x, e.shape.center_y);
+            if !e.entered {
+                e.entered = playfield.contains(x, y, 0.0);
+                if !e.entered && e.age > e.entry_time {
+                    e.kill(DeathCause::OutOfBounds);
+                }
+            } else if handle != player && !playfield.contains(x, y, DESPAWN_MARGIN) {
+                e.kill(DeathCause::OutOfBounds);
+            }
+            if e.is_expired() {
+                e.kill(DeathCause::Expired);
+            }
+        }
+
+        self.play_time += dt;
+        self.spawn_cycle(dt);
+
+        //everything that died this tick is removed last
+        self.reap();
+    }
+}
+
+//what kind of projectile an entity of a type fires
+fn projectile_type(shooter: EntityType) -> EntityType {
+    if shooter == EntityType::Player {
+        EntityType::PlayerProjectile
+    } else {
+        EntityType::EnemyProjectile
+    }
+}
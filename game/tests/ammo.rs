@@ -0,0 +1,200 @@
+mod common;
+
+use common::{count, equip, test_state};
+use game::weapon::{equip_weapon, find_weapon, parse_weapons};
+use game::{EntityType, Input, ModelId, PickupKind, State};
+
+//give the player a weapon with no cooldown, so only ammo and heat stop it firing
+fn equip_rules(state: &mut State, rules: &str) {
+    let text = format!(
+        r#"
+        [[weapon]]
+        name = "test"
+        model = "triangle"
+        speed = 600.0
+        {}
+
+        [[weapon]]
+        name = "beam"
+        kind = "beam"
+        model = "triangle"
+        dps = 10.0
+        range = 100.0
+        width = 4.0
+        heat_per_shot = 0.5
+        cooling = 0.25
+        overheat_time = 1.0
+        "#,
+        rules
+    );
+    equip(state, &text, "test");
+}
+
+fn ticks(state: &mut State, input: &Input, n: u32) {
+    for _ in 0..n {
+        state.tick(input);
+    }
+}
+
+#[test]
+fn weapons_without_ammo_or_heat_never_stop() {
+    let mut state = test_state();
+    for _ in 0..100 {
+        state.player_mut().weapon_cooldown = 0.0;
+        assert!(state.fire_weapon(state.p1));
+    }
+    assert_eq!(state.ammo(), None);
+    assert_eq!(state.heat(), None);
+}
+
+#[test]
+fn running_out_of_ammo_reloads() {
+    let mut state = test_state();
+    equip_rules(&mut state, "ammo = 3\nreload_time = 1.0");
+    assert_eq!(state.ammo(), Some((3, 3)));
+    for _ in 0..3 {
+        assert!(state.fire_weapon(state.p1));
+    }
+    assert_eq!(state.ammo(), Some((0, 3)));
+    assert!(state.reloading());
+    assert!(!state.fire_weapon(state.p1));
+
+    ticks(&mut state, &Input::default(), 55);
+    assert!(state.reloading());
+    ticks(&mut state, &Input::default(), 10);
+    assert!(!state.reloading());
+    assert_eq!(state.ammo(), Some((3, 3)));
+    assert!(state.fire_weapon(state.p1));
+    assert_eq!(state.ammo(), Some((2, 3)));
+}
+
+#[test]
+fn heat_builds_up_cools_off_and_locks_the_weapon_when_full() {
+    let mut state = test_state();
+    equip_rules(
+        &mut state,
+        "heat_per_shot = 0.4\ncooling = 0.5\noverheat_time = 1.0",
+    );
+    assert_eq!(state.heat(), Some(0.0));
+    state.fire_weapon(state.p1);
+    state.fire_weapon(state.p1);
+    assert!((state.heat().unwrap() - 0.8).abs() < 0.001);
+
+    //half a second takes a quarter of the heat away
+    ticks(&mut state, &Input::default(), 30);
+    assert!((state.heat().unwrap() - 0.55).abs() < 0.01);
+
+    state.fire_weapon(state.p1);
+    assert!(!state.overheated());
+    state.fire_weapon(state.p1);
+    assert!(state.overheated());
+    assert_eq!(state.heat(), Some(1.0));
+    assert!(!state.fire_weapon(state.p1));
+
+    //no cooling while overheated. the heat is all gone once it's over
+    ticks(&mut state, &Input::default(), 30);
+    assert_eq!(state.heat(), Some(1.0));
+    ticks(&mut state, &Input::default(), 35);
+    assert!(!state.overheated());
+    assert_eq!(state.heat(), Some(0.0));
+    assert!(state.fire_weapon(state.p1));
+}
+
+#[test]
+fn beams_heat_up_while_held_and_cut_out_when_overheated() {
+    let mut state = test_state();
+    equip_rules(&mut state, "");
+    state.player_mut().wtype = equip_weapon(&state.weapons, "beam");
+    let fire = Input {
+        fire: true,
+        ..Input::default()
+    };
+    //heat goes up by 0.5 a second and down by 0.25, so it takes four seconds to fill
+    ticks(&mut state, &fire, 120);
+    assert!(!state.beams.is_empty());
+    assert!((state.heat().unwrap() - 0.5).abs() < 0.02);
+    ticks(&mut state, &fire, 125);
+    assert!(state.overheated());
+    assert!(state.beams.is_empty());
+}
+
+#[test]
+fn picking_up_a_weapon_starts_it_full_and_cold() {
+    let mut state = test_state();
+    equip_rules(&mut state, "ammo = 2\nreload_time = 5.0");
+    state.fire_weapon(state.p1);
+    state.fire_weapon(state.p1);
+    assert!(state.reloading());
+
+    let test = find_weapon(&state.weapons, "test").unwrap();
+    let (x, y) = (state.player().shape.center_x, state.player().shape.center_y);
+    state.spawn_pickup(PickupKind::Weapon(test), ModelId(0), x, y);
+    state.tick(&Input::default());
+    assert!(!state.reloading());
+    assert_eq!(state.ammo(), Some((2, 2)));
+}
+
+#[test]
+fn ammo_and_heat_need_times_to_recover() {
+    let state = test_state();
+    for rules in [
+        "ammo = 3",
+        "heat_per_shot = 0.5",
+        "heat_per_shot = 0.5\ncooling = -1.0\noverheat_time = 1.0",
+    ] {
+        let text = format!(
+            "[[weapon]]\nname = \"test\"\nmodel = \"triangle\"\nspeed = 600.0\n{}",
+            rules
+        );
+        let error = parse_weapons(&text, &state.models).unwrap_err();
+        assert!(error.contains("\"test\""), "{}", error);
+    }
+    let beam = r#"
+        [[weapon]]
+        name = "beam"
+        kind = "beam"
+        model = "triangle"
+        dps = 10.0
+        range = 100.0
+        width = 4.0
+        ammo = 3
+        reload_time = 1.0
+    "#;
+    assert!(parse_weapons(beam, &state.models).is_err());
+}
+
+#[test]
+fn charged_shots_use_ammo_and_heat_too() {
+    let mut state = test_state();
+    equip_rules(
+        &mut state,
+        "ammo = 2\nreload_time = 5.0\nheat_per_shot = 0.4\noverheat_time = 5.0\ncooldown = 0.1\ncharge_time = 0.2\ncharged = [\"big\"]\n\n[[weapon]]\nname = \"big\"\nmodel = \"triangle\"\nspeed = 400.0\ncooldown = 0.5",
+    );
+    let press = Input {
+        fire: true,
+        fire_pressed: true,
+        ..Input::default()
+    };
+    let hold = Input {
+        fire: true,
+        ..Input::default()
+    };
+    let release = Input {
+        fire_released: true,
+        ..Input::default()
+    };
+    //the press fires one shot, and letting go after charging fires the other
+    state.tick(&press);
+    ticks(&mut state, &hold, 30);
+    assert_eq!(state.ammo(), Some((1, 2)));
+    state.tick(&release);
+    assert!(state.reloading());
+    assert!((state.heat().unwrap() - 0.8).abs() < 0.05);
+    assert!(state.player().weapon_cooldown > 0.4);
+
+    //a charge let go while reloading fires nothing
+    ticks(&mut state, &hold, 30);
+    let shots = count(&state, EntityType::PlayerProjectile);
+    state.tick(&release);
+    assert!(count(&state, EntityType::PlayerProjectile) <= shots);
+}
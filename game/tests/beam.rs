@@ -0,0 +1,119 @@
+mod common;
+
+use common::{equip, test_state};
+use game::{EntityHandle, EntityType, Input, ModelId, ShipDraw, State};
+
+//give the player a beam that does dps damage per second
+fn equip_beam(state: &mut State, dps: f32) {
+    let text = format!(
+        r#"
+        [[weapon]]
+        name = "beam"
+        kind = "beam"
+        model = "triangle"
+        dps = {}
+        range = 400.0
+        width = 4.0
+        "#,
+        dps
+    );
+    equip(state, &text, "beam");
+    state.player_mut().shape = ShipDraw::new(ModelId(0), 500.0, 500.0);
+}
+
+//spawn something with plenty of health somewhere
+fn target_at(state: &mut State, etype: EntityType, x: f32, y: f32) -> EntityHandle {
+    let hitbox = state.models[1].aabb;
+    state.create_entity(
+        etype,
+        game::WeaponType::None,
+        1000,
+        ShipDraw::new(ModelId(0), x, y),
+        hitbox,
+        0.0,
+        0.0,
+        0.0,
+    )
+}
+
+fn health(state: &State, handle: EntityHandle) -> i32 {
+    state.entities.get(handle).unwrap().health
+}
+
+const FIRE: Input = Input {
+    up: false,
+    down: false,
+    left: false,
+    right: false,
+    fire: true,
+    fire_pressed: false,
+    fire_released: false,
+};
+
+#[test]
+fn beams_stop_at_the_first_enemy_and_damage_it_every_second() {
+    let mut state = test_state();
+    equip_beam(&mut state, 30.0);
+    let behind = target_at(&mut state, EntityType::Enemy, 500.0, 200.0);
+    let front = target_at(&mut state, EntityType::Enemy, 500.0, 300.0);
+    //off to the side, out of the beam's way
+    let aside = target_at(&mut state, EntityType::Enemy, 600.0, 300.0);
+
+    for _ in 0..60 {
+        state.tick(&FIRE);
+    }
+    assert_eq!(health(&state, front), 1000 - 30);
+    assert_eq!(health(&state, behind), 1000);
+    assert_eq!(health(&state, aside), 1000);
+
+    let beam = state.beams[0];
+    assert_eq!(beam.hit, Some(front));
+    //the beam reaches the bottom of the front enemy's hitbox, less half the beam's width
+    let (_, _, _, bottom) = state.entities.get(front).unwrap().bounds().bounding_box();
+    assert!((beam.end().1 - (bottom + 2.0)).abs() < 0.01);
+}
+
+#[test]
+fn beams_are_blocked_by_objects_but_pass_friendly_things() {
+    let mut state = test_state();
+    equip_beam(&mut state, 30.0);
+    let enemy = target_at(&mut state, EntityType::Enemy, 500.0, 100.0);
+    let wall = target_at(&mut state, EntityType::Object, 500.0, 300.0);
+    target_at(&mut state, EntityType::PlayerProjectile, 500.0, 400.0);
+
+    for _ in 0..60 {
+        state.tick(&FIRE);
+    }
+    assert_eq!(state.beams[0].hit, Some(wall));
+    assert_eq!(health(&state, wall), 1000 - 30);
+    assert_eq!(health(&state, enemy), 1000);
+}
+
+#[test]
+fn slow_beams_build_up_to_whole_points_of_damage() {
+    let mut state = test_state();
+    equip_beam(&mut state, 1.5);
+    let enemy = target_at(&mut state, EntityType::Enemy, 500.0, 300.0);
+    for _ in 0..30 {
+        state.tick(&FIRE);
+    }
+    assert_eq!(health(&state, enemy), 1000);
+    for _ in 0..95 {
+        state.tick(&FIRE);
+    }
+    assert_eq!(health(&state, enemy), 1000 - 3);
+}
+
+#[test]
+fn beams_only_fire_while_the_key_is_held() {
+    let mut state = test_state();
+    equip_beam(&mut state, 30.0);
+    state.tick(&FIRE);
+    assert_eq!(state.beams.len(), 1);
+    //nothing in the way, so it reaches its full range
+    assert_eq!(state.beams[0].hit, None);
+    assert_eq!(state.beams[0].length, 400.0);
+
+    state.tick(&Input::default());
+    assert!(state.beams.is_empty());
+}
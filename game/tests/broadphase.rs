@@ -0,0 +1,67 @@
+use game::broadphase::{brute_force_pairs, Grid, CELL_SIZE};
+use game::{Playfield, Shape};
+
+//scatter shapes over and a little past the playfield, using a fixed seed
+fn scatter(count: usize, seed: u32) -> Vec<Shape> {
+    let mut state = seed;
+    let mut next = move || {
+        state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+        (state >> 8) as f32 / (1 << 24) as f32
+    };
+    (0..count)
+        .map(|i| {
+            let x = next() * 1400.0 - 200.0;
+            let y = next() * 1000.0 - 200.0;
+            if i % 2 == 0 {
+                Shape::Circle {
+                    x,
+                    y,
+                    radius: 2.0 + next() * 60.0,
+                }
+            } else {
+                Shape::Aabb {
+                    x,
+                    y,
+                    half_width: 2.0 + next() * 100.0,
+                    half_height: 2.0 + next() * 20.0,
+                }
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn the_grid_finds_the_same_pairs_as_testing_everything() {
+    for seed in 0..5 {
+        let shapes = scatter(500, seed);
+        let mut grid = Grid::new(&Playfield::default(), 200.0, CELL_SIZE);
+        let mut found = Vec::new();
+        grid.pairs(&shapes, &mut found);
+        found.sort_unstable();
+
+        let mut expected = Vec::new();
+        brute_force_pairs(&shapes, &mut expected);
+        assert!(!expected.is_empty());
+        assert_eq!(found, expected);
+    }
+}
+
+#[test]
+fn shapes_far_outside_the_grid_still_collide() {
+    let shapes = [
+        Shape::Circle {
+            x: -5000.0,
+            y: 9000.0,
+            radius: 10.0,
+        },
+        Shape::Circle {
+            x: -5005.0,
+            y: 9000.0,
+            radius: 10.0,
+        },
+    ];
+    let mut grid = Grid::new(&Playfield::default(), 200.0, CELL_SIZE);
+    let mut found = Vec::new();
+    grid.pairs(&shapes, &mut found);
+    assert_eq!(found, vec![(0, 1)]);
+}
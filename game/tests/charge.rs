@@ -0,0 +1,172 @@
+mod common;
+
+use common::{count, equip, test_state};
+use game::weapon::parse_weapons;
+use game::{EntityType, Input, State};
+
+const DT: f32 = 1.0 / 60.0;
+
+//give the player a weapon that charges into bigger shots every half second held
+fn equip_charge(state: &mut State) {
+    let text = r#"
+        [[weapon]]
+        name = "basic"
+        model = "triangle"
+        speed = 600.0
+        cooldown = 0.1
+        charge_time = 0.5
+        charged = ["big", "bigger"]
+
+        [[weapon]]
+        name = "big"
+        model = "triangle"
+        speed = 400.0
+        punch_through = 3
+        size = 2.0
+
+        [[weapon]]
+        name = "bigger"
+        model = "triangle"
+        speed = 400.0
+        punch_through = 6
+        size = 4.0
+    "#;
+    equip(state, text, "basic");
+    //from the bottom of the screen, shots take a while to fly off the top
+    state.player_mut().shape.center_y = 590.0;
+}
+
+//the player projectile with the most punch through left
+fn biggest_shot(state: &State) -> Option<(i32, f32)> {
+    state
+        .entities
+        .values()
+        .filter(|e| e.etype == EntityType::PlayerProjectile)
+        .map(|e| (e.health, e.shape.scale))
+        .max_by_key(|&(health, _)| health)
+}
+
+fn press() -> Input {
+    Input {
+        fire: true,
+        fire_pressed: true,
+        ..Input::default()
+    }
+}
+
+fn hold() -> Input {
+    Input {
+        fire: true,
+        ..Input::default()
+    }
+}
+
+fn release() -> Input {
+    Input {
+        fire_released: true,
+        ..Input::default()
+    }
+}
+
+#[test]
+fn tapping_fires_a_basic_shot() {
+    let mut state = test_state();
+    equip_charge(&mut state);
+    state.update(&press(), DT);
+    state.update(&release(), DT);
+    assert_eq!(count(&state, EntityType::PlayerProjectile), 1);
+    assert_eq!(biggest_shot(&state), Some((1, 1.0)));
+}
+
+#[test]
+fn holding_charges_instead_of_repeating() {
+    let mut state = test_state();
+    equip_charge(&mut state);
+    state.update(&press(), DT);
+    for _ in 0..40 {
+        state.update(&hold(), DT);
+    }
+    assert_eq!(count(&state, EntityType::PlayerProjectile), 1);
+    assert_eq!(state.charge_level(), 1);
+    assert!(state.charge_progress() > 0.6 && state.charge_progress() < 0.7);
+
+    state.update(&release(), DT);
+    assert_eq!(count(&state, EntityType::PlayerProjectile), 2);
+    assert_eq!(biggest_shot(&state), Some((3, 2.0)));
+    assert_eq!(state.charge_level(), 0);
+    assert_eq!(state.charge_progress(), 0.0);
+}
+
+#[test]
+fn charge_stops_at_the_top_level() {
+    let mut state = test_state();
+    equip_charge(&mut state);
+    state.update(&press(), DT);
+    for _ in 0..300 {
+        state.update(&hold(), DT);
+    }
+    assert_eq!(state.charge_level(), 2);
+    assert_eq!(state.charge_progress(), 1.0);
+    state.update(&release(), DT);
+    assert_eq!(biggest_shot(&state), Some((6, 4.0)));
+}
+
+#[test]
+fn a_tap_between_ticks_is_not_lost() {
+    let mut state = test_state();
+    equip_charge(&mut state);
+    //the key goes down and comes back up during a frame too short to run a tick
+    let tap = Input {
+        fire_pressed: true,
+        fire_released: true,
+        ..Input::default()
+    };
+    assert_eq!(state.update(&tap, DT / 4.0), 0);
+    assert_eq!(count(&state, EntityType::PlayerProjectile), 0);
+    state.update(&Input::default(), DT);
+    assert_eq!(count(&state, EntityType::PlayerProjectile), 1);
+
+    //and it only fires once, not again every tick after
+    state.update(&Input::default(), DT * 3.0);
+    assert_eq!(count(&state, EntityType::PlayerProjectile), 1);
+}
+
+#[test]
+fn charged_weapons_must_exist() {
+    let state = test_state();
+    let text = r#"
+        [[weapon]]
+        name = "basic"
+        model = "triangle"
+        speed = 600.0
+        charge_time = 0.5
+        charged = ["missing"]
+    "#;
+    let error = parse_weapons(text, &state.models).unwrap_err();
+    assert!(error.contains("missing"), "{}", error);
+}
+
+#[test]
+fn beams_cant_be_charged() {
+    let state = test_state();
+    let beam = r#"
+        [[weapon]]
+        name = "beam"
+        kind = "beam"
+        model = "triangle"
+        dps = 10.0
+        range = 400.0
+        width = 4.0
+    "#;
+    let big = r#"
+        [[weapon]]
+        name = "big"
+        model = "triangle"
+        speed = 400.0
+    "#;
+    for extra in ["charge_time = 0.5\n", "charged = [\"big\"]\n"] {
+        let text = format!("{}{}{}", beam, extra, big);
+        let error = parse_weapons(&text, &state.models).unwrap_err();
+        assert!(error.contains("beams can't be charged"), "{}", error);
+    }
+}
@@ -0,0 +1,280 @@
+mod common;
+
+use common::test_state;
+use game::collision::{collides, shapes_from_vertices, swept_touches};
+use game::model::load_models;
+use game::{EntityHandle, EntityType, Input, Shape, State};
+use std::path::Path;
+
+fn circle(x: f32, y: f32, radius: f32) -> Shape {
+    Shape::Circle { x, y, radius }
+}
+
+fn aabb(x: f32, y: f32, half_width: f32, half_height: f32) -> Shape {
+    Shape::Aabb {
+        x,
+        y,
+        half_width,
+        half_height,
+    }
+}
+
+#[test]
+fn shapes_touch_when_they_overlap() {
+    assert!(circle(0.0, 0.0, 5.0).touches(&circle(9.0, 0.0, 5.0)));
+    assert!(!circle(0.0, 0.0, 5.0).touches(&circle(11.0, 0.0, 5.0)));
+
+    assert!(aabb(0.0, 0.0, 5.0, 5.0).touches(&aabb(9.0, 9.0, 5.0, 5.0)));
+    assert!(!aabb(0.0, 0.0, 5.0, 5.0).touches(&aabb(11.0, 0.0, 5.0, 5.0)));
+
+    //the corner of the box is farther away than its sides
+    assert!(circle(8.0, 0.0, 4.0).touches(&aabb(0.0, 0.0, 5.0, 5.0)));
+    assert!(!aabb(0.0, 0.0, 5.0, 5.0).touches(&circle(8.0, 8.0, 4.0)));
+}
+
+#[test]
+fn shapes_fit_around_model_vertices() {
+    let vertices = vec![
+        2.0, 1.0, 0.0, 0.0, 0.0, 1.0, //
+        4.0, 1.0, 0.0, 0.0, 0.0, 1.0, //
+        2.0, 5.0, 0.0, 0.0, 0.0, 1.0,
+    ];
+    let (c, b) = shapes_from_vertices(&vertices);
+    assert_eq!(b, aabb(3.0, 3.0, 1.0, 2.0));
+    match c {
+        Shape::Circle { x, y, radius } => {
+            assert_eq!((x, y), (3.0, 3.0));
+            assert!((radius - 5.0f32.sqrt()).abs() < 1e-5);
+        }
+        _ => panic!("expected a circle"),
+    }
+}
+
+#[test]
+fn the_asset_models_are_scaled_into_playfield_units() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../assets/models");
+    let models = load_models(&dir).unwrap();
+    let cube = models.iter().find(|m| m.name == "Cube").unwrap();
+    //the cube in the assets folder is a little lopsided, so allow some slack
+    let scale = game::model::MODEL_SCALE;
+    match cube.aabb {
+        Shape::Aabb {
+            half_width,
+            half_height,
+            ..
+        } => {
+            assert!((half_width - scale).abs() < scale * 0.05);
+            assert!((half_height - scale).abs() < scale * 0.05);
+        }
+        _ => panic!("expected a box"),
+    }
+}
+
+#[test]
+fn overlapping_entities_are_found_every_tick() {
+    let mut state = test_state();
+    state.spawn_archetype(0, 100.0, 100.0);
+    let enemy = enemy_handle(&state);
+    let shot = shoot_at(&mut state, enemy, 1);
+    state.tick(&Input::default());
+    assert!(state.contacts.contains(&(enemy, shot)) || state.contacts.contains(&(shot, enemy)));
+
+    state.spawn_archetype(0, 100.0, 100.0);
+    state.tick(&Input::default());
+    assert!(state.contacts.is_empty());
+}
+
+//the first enemy in the entity list
+fn enemy_handle(state: &State) -> EntityHandle {
+    state
+        .entities
+        .iter()
+        .find(|(_, e)| e.etype == EntityType::Enemy)
+        .map(|(h, _)| h)
+        .unwrap()
+}
+
+//fire a player projectile and put it right on top of a target
+fn shoot_at(state: &mut State, target: EntityHandle, punch_through: i32) -> EntityHandle {
+    let shot = state.bullet(state.p1)[0];
+    let (x, y) = {
+        let t = state.entities.get(target).unwrap();
+        (t.shape.center_x, t.shape.center_y)
+    };
+    let e = state.entities.get_mut(shot).unwrap();
+    e.shape.center_x = x;
+    e.shape.center_y = y;
+    e.speed_y = 0.0;
+    e.health = punch_through;
+    shot
+}
+
+#[test]
+fn the_collision_matrix_follows_the_entity_type_rules() {
+    use EntityType::*;
+    let types = [
+        Player,
+        PlayerProjectile,
+        Enemy,
+        EnemyProjectile,
+        Object,
+        Effect,
+        Pickup,
+    ];
+    let allowed = [
+        (Player, Enemy),
+        (Player, EnemyProjectile),
+        (PlayerProjectile, Enemy),
+        (Object, Player),
+        (Object, PlayerProjectile),
+        (Object, Enemy),
+        (Object, EnemyProjectile),
+        (Object, Object),
+        (Object, Effect),
+        (Effect, Player),
+        (Effect, Enemy),
+        (Effect, Effect),
+        (Pickup, Player),
+    ];
+    for a in types {
+        for b in types {
+            let expected = allowed.contains(&(a, b)) || allowed.contains(&(b, a));
+            assert_eq!(collides(a, b), expected, "{:?} vs {:?}", a, b);
+        }
+    }
+}
+
+#[test]
+fn projectiles_damage_what_they_hit_and_are_used_up() {
+    let mut state = test_state();
+    state.spawn_archetype(0, 100.0, 100.0);
+    let enemy = enemy_handle(&state);
+    let health = state.entities.get(enemy).unwrap().health;
+    let shot = shoot_at(&mut state, enemy, 1);
+    state.tick(&Input::default());
+
+    assert_eq!(state.entities.get(enemy).unwrap().health, health - 1);
+    assert!(!state.entities.contains(shot));
+}
+
+#[test]
+fn punch_through_lets_a_projectile_hit_several_targets_once_each() {
+    let mut state = test_state();
+    state.spawn_archetype(0, 100.0, 100.0);
+    state.spawn_archetype(0, 100.0, 100.0);
+    let enemies: Vec<EntityHandle> = state
+        .entities
+        .iter()
+        .filter(|(_, e)| e.etype == EntityType::Enemy)
+        .map(|(h, _)| h)
+        .collect();
+    let health = state.entities.get(enemies[0]).unwrap().health;
+    let shot = shoot_at(&mut state, enemies[0], 3);
+    for _ in 0..5 {
+        state.tick(&Input::default());
+    }
+
+    //both stacked enemies are hit a single time, leaving the projectile with one hit left
+    for enemy in enemies {
+        assert_eq!(state.entities.get(enemy).unwrap().health, health - 1);
+    }
+    assert_eq!(state.entities.get(shot).unwrap().health, 1);
+}
+
+#[test]
+fn ships_that_touch_damage_each_other() {
+    let mut state = test_state();
+    state.spawn_archetype(0, 100.0, 100.0);
+    let enemy = enemy_handle(&state);
+    let health = state.entities.get(enemy).unwrap().health;
+    let (x, y) = {
+        let e = state.entities.get(enemy).unwrap();
+        (e.shape.center_x, e.shape.center_y)
+    };
+    state.player_mut().shape.center_x = x;
+    state.player_mut().shape.center_y = y;
+    state.tick(&Input::default());
+
+    assert_eq!(state.entities.get(enemy).unwrap().health, health - 1);
+    assert!(state
+        .deaths
+        .iter()
+        .any(|d| d.etype == EntityType::Player && d.killer == Some(enemy)));
+}
+
+#[test]
+fn intangible_entities_are_ignored() {
+    let mut state = test_state();
+    state.spawn_archetype(0, 100.0, 100.0);
+    let enemy = enemy_handle(&state);
+    state.entities.get_mut(enemy).unwrap().is_tangible = false;
+    shoot_at(&mut state, enemy, 1);
+    state.tick(&Input::default());
+    assert!(state.contacts.is_empty());
+}
+
+#[test]
+fn moving_shapes_hit_things_they_pass_through() {
+    let wall = aabb(0.0, 0.0, 50.0, 1.0);
+    //a bullet that jumped from above the wall to below it in one tick
+    let bullet = circle(0.0, 40.0, 2.0);
+    assert!(!bullet.touches(&wall));
+    assert!(swept_touches(&bullet, 0.0, 80.0, &wall));
+    //the same jump, but off to the side of the wall
+    assert!(!swept_touches(&circle(60.0, 40.0, 2.0), 0.0, 80.0, &wall));
+
+    let target = circle(0.0, 0.0, 3.0);
+    assert!(swept_touches(&bullet, 0.0, 80.0, &target));
+    assert!(!swept_touches(&circle(6.0, 40.0, 2.0), 0.0, 80.0, &target));
+
+    //a box sweeping diagonally past the corner of another one
+    let moving = aabb(20.0, -20.0, 1.0, 1.0);
+    assert!(!swept_touches(
+        &moving,
+        40.0,
+        40.0,
+        &aabb(0.0, 0.0, 5.0, 5.0)
+    ));
+    assert!(swept_touches(
+        &moving,
+        40.0,
+        -40.0,
+        &aabb(0.0, 0.0, 5.0, 5.0)
+    ));
+}
+
+#[test]
+fn fast_projectiles_hit_thin_enemies_at_any_tick_rate() {
+    for tick_rate in [30.0, 60.0, 240.0] {
+        let mut state = test_state();
+        state.set_tick_rate(tick_rate);
+        state.spawn_archetype(0, 100.0, 100.0);
+        let enemy = enemy_handle(&state);
+        let health = {
+            let e = state.entities.get_mut(enemy).unwrap();
+            e.hitbox = aabb(0.0, 0.0, 20.0, 0.5);
+            e.health
+        };
+        let (x, y) = {
+            let e = state.entities.get(enemy).unwrap();
+            (e.shape.center_x, e.shape.center_y)
+        };
+
+        let shot = state.bullet(state.p1)[0];
+        let e = state.entities.get_mut(shot).unwrap();
+        e.hitbox = circle(0.0, 0.0, 0.5);
+        e.shape.center_x = x;
+        e.shape.center_y = y + 100.0;
+        e.shape.save_position();
+        e.speed_y = -20000.0;
+        for _ in 0..3 {
+            state.tick(&Input::default());
+        }
+        assert_eq!(
+            state.entities.get(enemy).unwrap().health,
+            health - 1,
+            "missed at {} ticks per second",
+            tick_rate
+        );
+    }
+}
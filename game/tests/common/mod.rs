@@ -0,0 +1,81 @@
+//shared by every test file, but not every file uses every helper
+#![allow(dead_code)]
+
+use game::enemy::parse_enemies;
+use game::stage::parse_stage;
+use game::state::PLAYER_MODEL;
+use game::weapon::{equip_weapon, parse_weapons};
+use game::{EnemyDef, EntityType, ModelData, Stage, State, WeaponDef};
+
+//a single triangle stands in for the real models so the tests don't need the assets folder
+//the second copy goes by the player's model name, so the player has a ship to fly
+pub fn test_models() -> Vec<ModelData> {
+    let model = ModelData::new(
+        "triangle".to_string(),
+        vec![
+            0.0, 1.0, 0.0, 0.0, 0.0, 1.0, //
+            -1.0, -1.0, 0.0, 0.0, 0.0, 1.0, //
+            1.0, -1.0, 0.0, 0.0, 0.0, 1.0,
+        ],
+        vec![0, 1, 2],
+    );
+    let mut player = model.clone();
+    player.name = PLAYER_MODEL.to_string();
+    vec![model, player]
+}
+
+//a plain single shot weapon for the player, like the one in the assets folder
+pub fn test_weapons(models: &[ModelData]) -> Vec<WeaponDef> {
+    let text = r#"
+        [[weapon]]
+        name = "player_basic"
+        model = "triangle"
+        speed = 600.0
+        cooldown = 0.1
+    "#;
+    parse_weapons(text, models).unwrap()
+}
+
+//swap in the weapons from some text written the same way as the weapons file,
+//and give the player the one called name
+pub fn equip(state: &mut State, text: &str, name: &str) {
+    state.weapons = parse_weapons(text, &state.models).unwrap();
+    state.player_mut().wtype = equip_weapon(&state.weapons, name);
+}
+
+//a single unarmed enemy that never drops anything
+pub fn test_enemies(models: &[ModelData], weapons: &[WeaponDef]) -> Vec<EnemyDef> {
+    let text = r#"
+        [[enemy]]
+        name = "grunt"
+        model = "triangle"
+        health = 5
+    "#;
+    parse_enemies(text, models, weapons).unwrap()
+}
+
+//a single wave of five grunts, ten seconds in
+pub fn test_stage(enemies: &[EnemyDef]) -> Stage {
+    let text = r#"
+        [[wave]]
+        time = 10.0
+        enemy = "grunt"
+        count = 5
+        entry = [500.0, 100.0]
+    "#;
+    parse_stage(text, enemies).unwrap()
+}
+
+//a fresh game using the test models, weapons, enemies and stage
+pub fn test_state() -> State {
+    let models = test_models();
+    let weapons = test_weapons(&models);
+    let enemies = test_enemies(&models, &weapons);
+    let stage = test_stage(&enemies);
+    State::new(models, weapons, enemies, stage).unwrap()
+}
+
+//how many entities of a type are in the game
+pub fn count(state: &State, etype: EntityType) -> usize {
+    state.entities.values().filter(|e| e.etype == etype).count()
+}
@@ -0,0 +1,175 @@
+mod common;
+
+use common::{count, test_state};
+use game::stage::parse_stage;
+use game::weapon::{equip_weapon, parse_weapons};
+use game::{Difficulty, EntityType, Input, State};
+
+fn curve() -> Difficulty {
+    Difficulty {
+        score_per_level: 1000.0,
+        seconds_per_level: 60.0,
+        max_level: 5.0,
+        wave_size: 0.5,
+        spawn_rate: 1.0,
+        health: 0.25,
+        fire_rate: 0.5,
+        projectile_speed: 0.1,
+    }
+}
+
+fn close(a: f32, b: f32) -> bool {
+    (a - b).abs() < 0.0001
+}
+
+#[test]
+fn score_and_time_add_up_to_the_level() {
+    let curve = curve();
+    assert_eq!(curve.level(0, 0.0), 0.0);
+    assert!(close(curve.level(500, 0.0), 0.5));
+    assert!(close(curve.level(0, 90.0), 1.5));
+    assert!(close(curve.level(2000, 60.0), 3.0));
+    //the level stops at the top
+    assert_eq!(curve.level(100000, 0.0), 5.0);
+    //a negative score doesn't make things easier than the start
+    assert_eq!(curve.level(-5000, 0.0), 0.0);
+}
+
+#[test]
+fn each_level_scales_everything_by_its_rate() {
+    let scale = curve().scale(2000, 0.0);
+    assert_eq!(scale.level, 2.0);
+    assert!(close(scale.wave_size, 2.0));
+    assert!(close(scale.spawn_interval, 1.0 / 3.0));
+    assert!(close(scale.health, 1.5));
+    assert!(close(scale.fire_rate, 2.0));
+    assert!(close(scale.projectile_speed, 1.2));
+
+    assert_eq!(scale.wave_count(3), 6);
+    assert_eq!(scale.enemy_health(5), 8);
+}
+
+#[test]
+fn the_default_curve_is_flat() {
+    let scale = Difficulty::default().scale(1000000, 10000.0);
+    assert_eq!(scale.level, 0.0);
+    for multiplier in [
+        scale.wave_size,
+        scale.spawn_interval,
+        scale.health,
+        scale.fire_rate,
+        scale.projectile_speed,
+    ] {
+        assert_eq!(multiplier, 1.0);
+    }
+}
+
+//a stage with a wave of two grunts every ten seconds, getting a level harder every 1000 points
+fn harder_stage(state: &mut State) {
+    let text = r#"
+        repeat = 10.0
+
+        [difficulty]
+        score_per_level = 1000.0
+        wave_size = 0.5
+        spawn_rate = 1.0
+        health = 1.0
+
+        [[wave]]
+        time = 5.0
+        enemy = "grunt"
+        count = 2
+        entry = [500.0, 100.0]
+    "#;
+    state.stage = parse_stage(text, &state.enemies).unwrap();
+}
+
+fn enemy_health(state: &State) -> Vec<i32> {
+    state
+        .entities
+        .values()
+        .filter(|e| e.etype == EntityType::Enemy)
+        .map(|e| e.health)
+        .collect()
+}
+
+#[test]
+fn waves_get_bigger_tougher_and_sooner_with_score() {
+    let mut state = test_state();
+    harder_stage(&mut state);
+    for _ in 0..310 {
+        state.tick(&Input::default());
+    }
+    assert_eq!(enemy_health(&state), vec![5, 5]);
+
+    //at level 2, waves are twice as big, enemies have three times the health,
+    //and the timeline runs three times as fast, so the next wave is 10 seconds of timeline away
+    //but only about 3.3 seconds of play
+    state.score = 2000;
+    for _ in 0..190 {
+        state.tick(&Input::default());
+    }
+    assert_eq!(count(&state, EntityType::Enemy), 2);
+    for _ in 0..20 {
+        state.tick(&Input::default());
+    }
+    let mut health = enemy_health(&state);
+    health.sort();
+    assert_eq!(health, vec![5, 5, 15, 15, 15, 15]);
+}
+
+#[test]
+fn enemies_fire_faster_shots_more_often_with_difficulty() {
+    let mut state = test_state();
+    let weapons = r#"
+        [[weapon]]
+        name = "player_basic"
+        model = "triangle"
+        speed = 600.0
+        cooldown = 0.1
+
+        [[weapon]]
+        name = "enemy"
+        model = "triangle"
+        speed = 100.0
+        cooldown = 1.0
+    "#;
+    state.weapons = parse_weapons(weapons, &state.models).unwrap();
+    state.stage.difficulty = Difficulty {
+        seconds_per_level: 10.0,
+        fire_rate: 1.0,
+        projectile_speed: 0.5,
+        ..Difficulty::default()
+    };
+    state.play_time = 10.0;
+
+    let enemy = state.spawn_archetype(0, 100.0, 100.0);
+    let e = state.entities.get_mut(enemy).unwrap();
+    e.wtype = equip_weapon(&state.weapons, "enemy");
+    e.weapon_cooldown = 0.0;
+    assert!(state.fire_weapon(enemy));
+
+    //level 1: twice the fire rate halves the cooldown, and shots go half again as fast
+    let e = state.entities.get(enemy).unwrap();
+    assert!(close(e.weapon_cooldown, 0.5));
+    let shot = state
+        .entities
+        .values()
+        .find(|e| e.etype == EntityType::EnemyProjectile)
+        .unwrap();
+    assert!(close(shot.speed_y, 150.0));
+
+    //the player's weapon doesn't change
+    state.player_mut().weapon_cooldown = 0.0;
+    assert!(state.fire_weapon(state.p1));
+    assert!(close(state.player().weapon_cooldown, 0.1));
+}
+
+#[test]
+fn negative_difficulty_is_an_error_on_its_line() {
+    let state = test_state();
+    let text = "[difficulty]\nhealth = 0.5\nfire_rate = -1.0\n\n[[wave]]\ntime = 1.0\nenemy = \"grunt\"\nentry = [0.0, 0.0]\n";
+    let error = parse_stage(text, &state.enemies).unwrap_err();
+    assert!(error.contains("line 1"), "{}", error);
+    assert!(error.contains("negative"), "{}", error);
+}
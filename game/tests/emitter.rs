@@ -0,0 +1,147 @@
+mod common;
+
+use common::{count, test_state};
+use game::{Emitter, EntityType, Input, ModelId, SpeedCurve};
+use std::rc::Rc;
+
+const DT: f32 = 1.0 / 60.0;
+
+#[test]
+fn volleys_come_out_every_interval() {
+    let mut emitter = Emitter::new(ModelId(0), 4, 0.5, 100.0);
+    let mut fired = Vec::new();
+    for _ in 0..70 {
+        fired.push(emitter.update(DT).len());
+    }
+    //the first volley comes out right away, then one every half second
+    assert_eq!(fired[0], 4);
+    assert_eq!(fired[1..25].iter().sum::<usize>(), 0);
+    assert_eq!(fired[25..35].iter().sum::<usize>(), 4);
+    assert_eq!(fired[35..55].iter().sum::<usize>(), 0);
+    assert_eq!(fired[55..].iter().sum::<usize>(), 4);
+}
+
+#[test]
+fn arms_are_spaced_evenly_and_turn_over_time() {
+    let mut emitter = Emitter::new(ModelId(0), 4, 1.0, 100.0);
+    emitter.offset = 10.0;
+    emitter.rotation_speed = 90.0;
+    let first = emitter.update(DT);
+    let degrees: Vec<f32> = first.iter().map(|a| a.to_degrees()).collect();
+    for (i, d) in degrees.iter().enumerate() {
+        assert!((d - (10.0 + 90.0 * i as f32)).abs() < 0.01);
+    }
+
+    //after a second the whole pattern has turned a quarter of the way around
+    let mut next = Vec::new();
+    for _ in 0..60 {
+        next = emitter.update(DT);
+        if !next.is_empty() {
+            break;
+        }
+    }
+    assert!((next[0].to_degrees() - 100.0).abs() < 0.5);
+}
+
+#[test]
+fn arcs_fan_out_around_the_offset() {
+    let mut emitter = Emitter::new(ModelId(0), 3, 1.0, 100.0);
+    emitter.arc = 60.0;
+    let degrees: Vec<f32> = emitter.update(DT).iter().map(|a| a.to_degrees()).collect();
+    assert!((degrees[0] + 30.0).abs() < 0.01);
+    assert!(degrees[1].abs() < 0.01);
+    assert!((degrees[2] - 30.0).abs() < 0.01);
+}
+
+#[test]
+fn bursts_pause_between_them() {
+    let mut emitter = Emitter::new(ModelId(0), 1, 0.1, 100.0);
+    emitter.burst = 3;
+    emitter.burst_pause = 1.0;
+    let mut fired = Vec::new();
+    for _ in 0..120 {
+        fired.push(emitter.update(DT).len());
+    }
+    //three volleys over a fifth of a second, then a second's rest on top of the interval, then the next burst
+    assert_eq!(fired[..20].iter().sum::<usize>(), 3);
+    assert_eq!(fired[20..75].iter().sum::<usize>(), 0);
+    assert_eq!(fired[75..].iter().sum::<usize>(), 3);
+}
+
+#[test]
+fn speed_curves_blend_between_points() {
+    let curve = SpeedCurve {
+        points: vec![(0.0, 100.0), (1.0, 0.0), (2.0, 300.0)],
+    };
+    assert_eq!(curve.speed_at(0.0), 100.0);
+    assert_eq!(curve.speed_at(0.5), 50.0);
+    assert_eq!(curve.speed_at(1.0), 0.0);
+    assert_eq!(curve.speed_at(1.5), 150.0);
+    assert_eq!(curve.speed_at(10.0), 300.0);
+}
+
+#[test]
+fn an_emitter_on_an_enemy_spawns_a_ring_of_projectiles() {
+    let mut state = test_state();
+    let enemy = state.spawn_archetype(0, 100.0, 100.0);
+    let (x, y) = {
+        let e = state.entities.get_mut(enemy).unwrap();
+        e.emitter = Some(Emitter::new(ModelId(0), 4, 1.0, 60.0));
+        (e.shape.center_x, e.shape.center_y)
+    };
+
+    //after half a second, one ring has moved out 30 units in each direction
+    for _ in 0..30 {
+        state.tick(&Input::default());
+    }
+    assert_eq!(count(&state, EntityType::EnemyProjectile), 4);
+    let mut offsets: Vec<(i32, i32)> = state
+        .entities
+        .values()
+        .filter(|e| e.etype == EntityType::EnemyProjectile)
+        .map(|e| {
+            (
+                (e.shape.center_x - x).round() as i32,
+                (e.shape.center_y - y).round() as i32,
+            )
+        })
+        .collect();
+    offsets.sort();
+    assert_eq!(offsets, vec![(-30, 0), (0, -30), (0, 30), (30, 0)]);
+
+    //after another second, a second ring has come out
+    for _ in 0..60 {
+        state.tick(&Input::default());
+    }
+    assert_eq!(count(&state, EntityType::EnemyProjectile), 8);
+}
+
+#[test]
+fn emitted_projectiles_follow_the_speed_curve() {
+    let mut state = test_state();
+    let enemy = state.spawn_archetype(0, 100.0, 100.0);
+    let mut emitter = Emitter::new(ModelId(0), 1, 10.0, 0.0);
+    //fire straight down, slow to a stop, then take off again
+    emitter.speed = Rc::new(SpeedCurve {
+        points: vec![(0.0, 120.0), (0.5, 0.0), (1.0, 0.0), (1.5, 240.0)],
+    });
+    let curve = Rc::clone(&emitter.speed);
+    state.entities.get_mut(enemy).unwrap().emitter = Some(emitter);
+
+    let mut speeds = Vec::new();
+    for _ in 0..90 {
+        state.tick(&Input::default());
+        let shot = state
+            .entities
+            .values()
+            .find(|e| e.etype == EntityType::EnemyProjectile)
+            .unwrap();
+        assert_eq!(shot.speed_x, 0.0);
+        //the shot uses the emitter's curve rather than a copy of it
+        assert!(Rc::ptr_eq(shot.speed_curve.as_ref().unwrap(), &curve));
+        speeds.push(shot.speed_y);
+    }
+    assert!(speeds[0] > 100.0);
+    assert_eq!(speeds[45], 0.0);
+    assert!(speeds[89] > 200.0);
+}
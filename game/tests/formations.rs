@@ -0,0 +1,215 @@
+mod common;
+
+use common::{count, test_state};
+use game::stage::{parse_stage, Entry, Formation, Side, ENTRY_MARGIN};
+use game::{DeathCause, EntityType, Input, Playfield, Rng, State};
+
+fn offsets(formation: Formation, count: u32) -> Vec<(f32, f32)> {
+    formation.offsets(count, 10.0, 1000.0, &mut Rng::new(1))
+}
+
+fn distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+#[test]
+fn formations_lay_out_their_shapes() {
+    assert_eq!(
+        offsets(Formation::Line, 3),
+        vec![(-10.0, 0.0), (0.0, 0.0), (10.0, 0.0)]
+    );
+    assert_eq!(
+        offsets(Formation::V, 5),
+        vec![
+            (0.0, 0.0),
+            (-10.0, -10.0),
+            (10.0, -10.0),
+            (-20.0, -20.0),
+            (20.0, -20.0)
+        ]
+    );
+    assert_eq!(
+        offsets(Formation::Column, 3),
+        vec![(0.0, 0.0), (0.0, -10.0), (0.0, -20.0)]
+    );
+
+    let ring = offsets(Formation::Circle, 6);
+    for i in 0..6 {
+        assert!((distance(ring[i], ring[(i + 1) % 6]) - 10.0).abs() < 0.001);
+        assert!((distance(ring[i], (0.0, 0.0)) - 10.0).abs() < 0.001);
+    }
+
+    let scattered = offsets(Formation::Edge, 20);
+    assert!(scattered
+        .iter()
+        .all(|&(x, y)| (-500.0..500.0).contains(&x) && y == 0.0));
+    assert!(scattered.iter().any(|&(x, _)| x < -100.0));
+    assert!(scattered.iter().any(|&(x, _)| x > 100.0));
+}
+
+//a wave from a stage with only that wave in it
+fn wave(state: &State, fields: &str) -> game::WaveEvent {
+    let text = format!(
+        "[[wave]]\ntime = 0.0\nenemy = \"grunt\"\nspacing = 10.0\n{}",
+        fields
+    );
+    parse_stage(&text, &state.enemies).unwrap().waves.remove(0)
+}
+
+#[test]
+fn waves_from_an_edge_start_just_outside_it_facing_in() {
+    let state = test_state();
+    let playfield = Playfield::default();
+    let mut rng = Rng::new(1);
+
+    let from_top = wave(
+        &state,
+        "count = 3\nformation = \"column\"\nfrom = \"top\"\nat = 200.0\nspeed = 10.0",
+    );
+    assert_eq!(from_top.entry, Entry::Edge(Side::Top, Some(200.0)));
+    let positions = from_top.positions(3, &playfield, &mut rng);
+    assert_eq!(
+        positions,
+        vec![
+            (200.0, -ENTRY_MARGIN),
+            (200.0, -ENTRY_MARGIN - 10.0),
+            (200.0, -ENTRY_MARGIN - 20.0)
+        ]
+    );
+
+    //a column from the left trails off to the left, and starts halfway down by default
+    let from_left = wave(
+        &state,
+        "count = 3\nformation = \"column\"\nfrom = \"left\"\nspeed = 10.0",
+    );
+    let positions = from_left.positions(3, &playfield, &mut rng);
+    assert_eq!(positions[0], (-ENTRY_MARGIN, 300.0));
+    assert_eq!(positions[2], (-ENTRY_MARGIN - 20.0, 300.0));
+
+    //the tip of a v from the bottom points up the screen
+    let from_bottom = wave(
+        &state,
+        "count = 3\nformation = \"v\"\nfrom = \"bottom\"\nspeed = 10.0",
+    );
+    let positions = from_bottom.positions(3, &playfield, &mut rng);
+    assert_eq!(positions[0], (500.0, 600.0 + ENTRY_MARGIN));
+    assert!(positions[1..].iter().all(|p| p.1 == 610.0 + ENTRY_MARGIN));
+
+    //a circle is backed off far enough that all of it starts offscreen
+    let from_right = wave(
+        &state,
+        "count = 8\nformation = \"circle\"\nfrom = \"right\"\nspeed = 10.0",
+    );
+    let positions = from_right.positions(8, &playfield, &mut rng);
+    assert!(positions
+        .iter()
+        .all(|p| p.0 >= 1000.0 + ENTRY_MARGIN - 0.001));
+
+    let scattered = wave(
+        &state,
+        "count = 10\nformation = \"edge\"\nfrom = \"top\"\nspeed = 10.0",
+    );
+    let positions = scattered.positions(10, &playfield, &mut rng);
+    assert!(positions
+        .iter()
+        .all(|&(x, y)| (0.0..1000.0).contains(&x) && y == -ENTRY_MARGIN));
+}
+
+#[test]
+fn long_formations_fly_in_without_being_removed_offscreen() {
+    let mut state = test_state();
+    //the back of this column starts much further out than things are normally allowed to go
+    let text = r#"
+        [[wave]]
+        time = 0.0
+        enemy = "grunt"
+        count = 8
+        formation = "column"
+        spacing = 60.0
+        from = "top"
+        speed = 300.0
+    "#;
+    state.stage = parse_stage(text, &state.enemies).unwrap();
+    state.tick(&Input::default());
+    assert_eq!(count(&state, EntityType::Enemy), 8);
+    assert!(state
+        .entities
+        .values()
+        .filter(|e| e.etype == EntityType::Enemy)
+        .all(|e| e.shape.center_y < 0.0));
+
+    for _ in 0..120 {
+        state.tick(&Input::default());
+    }
+    assert_eq!(count(&state, EntityType::Enemy), 8);
+    assert!(state
+        .entities
+        .values()
+        .filter(|e| e.etype == EntityType::Enemy)
+        .all(|e| e.entered));
+
+    //once in, they leave like anything else
+    for _ in 0..300 {
+        state.tick(&Input::default());
+    }
+    assert_eq!(count(&state, EntityType::Enemy), 0);
+}
+
+#[test]
+fn edge_waves_that_never_make_it_in_are_removed_eventually() {
+    let mut state = test_state();
+    //the dive turns around before it gets anywhere near the playfield
+    let text = r#"
+        [[wave]]
+        time = 0.0
+        enemy = "grunt"
+        from = "top"
+        pattern = "dive"
+        period = 0.1
+        speed = 100.0
+    "#;
+    state.stage = parse_stage(text, &state.enemies).unwrap();
+    state.tick(&Input::default());
+    assert_eq!(count(&state, EntityType::Enemy), 1);
+
+    let mut removed = false;
+    for _ in 0..60 * 10 {
+        state.tick(&Input::default());
+        removed |= state
+            .deaths
+            .iter()
+            .any(|d| d.etype == EntityType::Enemy && d.cause == DeathCause::OutOfBounds);
+    }
+    assert!(removed);
+    assert_eq!(count(&state, EntityType::Enemy), 0);
+}
+
+#[test]
+fn edge_waves_have_to_head_into_the_playfield() {
+    let state = test_state();
+    for pattern in ["left_to_right", "still"] {
+        let text = format!(
+            "[[wave]]\ntime = 0.0\nenemy = \"grunt\"\nfrom = \"top\"\nspeed = 10.0\npattern = \"{}\"",
+            pattern
+        );
+        let error = parse_stage(&text, &state.enemies).unwrap_err();
+        assert!(error.contains("line 6"), "{}", error);
+        assert!(error.contains("doesn't head into"), "{}", error);
+    }
+}
+
+#[test]
+fn waves_need_one_place_to_come_in() {
+    let state = test_state();
+    for (fields, line) in [
+        ("", "line 1"),
+        ("entry = [0.0, 0.0]\nfrom = \"top\"\nspeed = 10.0", "line 5"),
+        ("entry = [0.0, 0.0]\nformation = \"edge\"", "line 5"),
+        ("from = \"top\"", "line 1"),
+        ("entry = [0.0, 0.0]\nat = 5.0", "line 5"),
+    ] {
+        let text = format!("[[wave]]\ntime = 0.0\nenemy = \"grunt\"\n{}", fields);
+        let error = parse_stage(&text, &state.enemies).unwrap_err();
+        assert!(error.contains(line), "{}", error);
+    }
+}
@@ -0,0 +1,119 @@
+mod common;
+
+use common::{equip, test_state};
+use game::{DeathCause, EntityType, Input, State};
+
+//give the player a homing weapon
+fn equip_missiles(state: &mut State, lifetime: f32) {
+    let text = format!(
+        r#"
+        [[weapon]]
+        name = "missile"
+        model = "triangle"
+        speed = 200.0
+        cooldown = 1.0
+        turn_rate = 180.0
+        lifetime = {}
+        "#,
+        lifetime
+    );
+    equip(state, &text, "missile");
+}
+
+//spawn an enemy somewhere
+fn enemy_at(state: &mut State, x: f32, y: f32) -> game::EntityHandle {
+    let enemy = state.spawn_archetype(0, 100.0, 100.0);
+    let e = state.entities.get_mut(enemy).unwrap();
+    e.shape.center_x = x;
+    e.shape.center_y = y;
+    e.shape.save_position();
+    enemy
+}
+
+#[test]
+fn nearest_and_radius_queries_find_living_entities() {
+    let mut state = test_state();
+    let far = enemy_at(&mut state, 800.0, 100.0);
+    let near = enemy_at(&mut state, 300.0, 100.0);
+    let nearer = enemy_at(&mut state, 200.0, 100.0);
+
+    assert_eq!(state.nearest(EntityType::Enemy, 0.0, 100.0), Some(nearer));
+    assert_eq!(state.nearest(EntityType::Enemy, 900.0, 100.0), Some(far));
+    assert_eq!(state.nearest(EntityType::Object, 0.0, 100.0), None);
+
+    let mut found = state.in_radius(250.0, 100.0, 60.0);
+    found.sort_by_key(|h| h.index);
+    assert_eq!(found, vec![near, nearer]);
+
+    //dead entities don't count, even before they are removed
+    state.despawn(nearer);
+    assert_eq!(state.nearest(EntityType::Enemy, 0.0, 100.0), Some(near));
+    assert_eq!(state.in_radius(250.0, 100.0, 60.0), vec![near]);
+}
+
+#[test]
+fn missiles_turn_no_faster_than_their_turn_rate() {
+    let mut state = test_state();
+    equip_missiles(&mut state, 0.0);
+    state.player_mut().shape.center_x = 500.0;
+    state.player_mut().shape.center_y = 500.0;
+    //straight to the right of the player, so the missile has to turn a quarter of the way around
+    enemy_at(&mut state, 900.0, 500.0);
+    let missile = state.bullet(state.p1)[0];
+
+    state.tick(&Input::default());
+    let e = state.entities.get(missile).unwrap();
+    let turned = e.speed_x.atan2(-e.speed_y).to_degrees();
+    assert!((turned - 180.0 / 60.0).abs() < 0.01, "turned {}", turned);
+    let speed = (e.speed_x * e.speed_x + e.speed_y * e.speed_y).sqrt();
+    assert!((speed - 200.0).abs() < 0.01);
+}
+
+#[test]
+fn missiles_chase_down_enemies_off_to_the_side() {
+    let mut state = test_state();
+    equip_missiles(&mut state, 0.0);
+    state.player_mut().shape.center_x = 500.0;
+    state.player_mut().shape.center_y = 500.0;
+    let enemy = enemy_at(&mut state, 800.0, 400.0);
+    let health = state.entities.get(enemy).unwrap().health;
+    let missile = state.bullet(state.p1)[0];
+
+    for _ in 0..180 {
+        state.tick(&Input::default());
+    }
+    assert!(!state.entities.contains(missile));
+    assert_eq!(state.entities.get(enemy).unwrap().health, health - 1);
+}
+
+#[test]
+fn missiles_pick_a_new_target_when_theirs_is_gone() {
+    let mut state = test_state();
+    equip_missiles(&mut state, 0.0);
+    let first = enemy_at(&mut state, 100.0, 300.0);
+    let second = enemy_at(&mut state, 700.0, 300.0);
+    let missile = state.bullet(state.p1)[0];
+
+    state.tick(&Input::default());
+    assert_eq!(state.entities.get(missile).unwrap().target, Some(first));
+    state.despawn(first);
+    state.tick(&Input::default());
+    state.tick(&Input::default());
+    assert_eq!(state.entities.get(missile).unwrap().target, Some(second));
+}
+
+#[test]
+fn missiles_expire_after_their_lifetime() {
+    let mut state = test_state();
+    equip_missiles(&mut state, 0.5);
+    let missile = state.bullet(state.p1)[0];
+    let mut cause = None;
+    for _ in 0..40 {
+        state.tick(&Input::default());
+        if let Some(death) = state.deaths.iter().find(|d| d.handle == missile) {
+            cause = Some(death.cause);
+        }
+    }
+    assert!(!state.entities.contains(missile));
+    assert_eq!(cause, Some(DeathCause::Expired));
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use common::{count, test_state};
+use game::{DeathCause, EntityType, Input};
+
+#[test]
+fn despawned_entities_stay_until_the_end_of_the_tick() {
+    let mut state = test_state();
+    let shot = state.bullet(state.p1)[0];
+    state.despawn(shot);
+    assert!(state.entities.contains(shot));
+
+    state.tick(&Input::default());
+    assert!(!state.entities.contains(shot));
+    assert_eq!(state.deaths.len(), 1);
+    assert_eq!(state.deaths[0].handle, shot);
+    assert_eq!(state.deaths[0].cause, DeathCause::Despawned);
+}
+
+#[test]
+fn destroyed_enemies_record_their_killer_and_award_score() {
+    let mut state = test_state();
+    state.spawn_archetype(0, 100.0, 100.0);
+    let (enemy, _) = state
+        .entities
+        .iter()
+        .find(|(_, e)| e.etype == EntityType::Enemy)
+        .unwrap();
+    let shot = state.bullet(state.p1)[0];
+
+    let e = state.entities.get_mut(enemy).unwrap();
+    e.damage(e.health, Some(shot));
+    state.tick(&Input::default());
+
+    assert_eq!(count(&state, EntityType::Enemy), 0);
+    let death = state.deaths.iter().find(|d| d.handle == enemy).unwrap();
+    assert_eq!(death.cause, DeathCause::Destroyed);
+    assert_eq!(death.killer, Some(shot));
+    assert!(state.score > 0);
+}
+
+#[test]
+fn entities_that_leave_the_playfield_are_removed() {
+    let mut state = test_state();
+    let shot = state.bullet(state.p1)[0];
+    state.entities.get_mut(shot).unwrap().speed_y = -10000.0;
+    for _ in 0..10 {
+        state.tick(&Input::default());
+    }
+    assert!(!state.entities.contains(shot));
+    assert_eq!(state.score, 0);
+}
+
+#[test]
+fn the_player_respawns_instead_of_being_removed() {
+    let mut state = test_state();
+    let start = (state.player().shape.center_x, state.player().shape.center_y);
+    state.player_mut().shape.center_x = 500.0;
+    state.player_mut().damage(100, None);
+    state.tick(&Input::default());
+
+    assert!(state.entities.contains(state.p1));
+    assert!(state.player().is_alive());
+    assert_eq!(state.player().shape.center_x, start.0);
+    assert_eq!(state.deaths[0].etype, EntityType::Player);
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use common::{test_enemies, test_models, test_stage, test_state, test_weapons};
+use game::model::find_model;
+use game::state::PLAYER_MODEL;
+use game::{EntityType, Input, ModelId, State};
+
+#[test]
+fn models_are_found_by_name() {
+    let mut state = test_state();
+    state.models[1].name = "square".to_string();
+    assert_eq!(find_model(&state.models, "triangle"), Some(ModelId(0)));
+    assert_eq!(find_model(&state.models, "square"), Some(ModelId(1)));
+    assert_eq!(find_model(&state.models, "missing"), None);
+}
+
+#[test]
+fn entities_share_the_loaded_models() {
+    let mut state = test_state();
+    let shots = [state.bullet(state.p1)[0], state.bullet(state.p1)[0]];
+    let enemy = state.spawn_archetype(0, 100.0, 100.0);
+    state.tick(&Input::default());
+
+    for shot in shots {
+        let e = state.entities.get(shot).unwrap();
+        assert_eq!(e.etype, EntityType::PlayerProjectile);
+        assert_eq!(e.shape.model, state.weapons[0].model_id);
+    }
+    let e = state.entities.get(enemy).unwrap();
+    assert_eq!(e.shape.model, state.enemies[0].model_id);
+
+    //drawing looks the mesh up instead of every entity carrying its own copy
+    let model = state.model(e.shape.model);
+    assert_eq!(model.indices, vec![0, 1, 2]);
+}
+
+#[test]
+fn the_player_uses_its_model_by_name() {
+    let state = test_state();
+    let player = find_model(&state.models, PLAYER_MODEL);
+    assert_eq!(player, Some(ModelId(1)));
+    assert_eq!(Some(state.player().shape.model), player);
+
+    //without it there's nothing to draw the player with
+    let models = test_models();
+    let weapons = test_weapons(&models);
+    let enemies = test_enemies(&models, &weapons);
+    let stage = test_stage(&enemies);
+    let without: Vec<_> = models
+        .into_iter()
+        .filter(|m| m.name != PLAYER_MODEL)
+        .collect();
+    match State::new(without, weapons, enemies, stage) {
+        Ok(_) => panic!("a state was made without the player's model"),
+        Err(error) => assert!(error.contains(PLAYER_MODEL), "{}", error),
+    }
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use common::test_state;
+use game::{Input, Playfield};
+
+//hold an input for a number of seconds at a given tick rate, returning how far the player moved
+fn distance_moved(input: Input, seconds: f32, tick_rate: f32) -> (f32, f32) {
+    let mut state = test_state();
+    state.set_tick_rate(tick_rate);
+    state.playfield = Playfield::new(100000.0, 100000.0);
+    state.player_mut().shape.center_x = 50000.0;
+    state.player_mut().shape.center_y = 50000.0;
+    let ticks = (seconds * tick_rate).round() as u32;
+    for _ in 0..ticks {
+        state.tick(&input);
+    }
+    (
+        state.player().shape.center_x - 50000.0,
+        state.player().shape.center_y - 50000.0,
+    )
+}
+
+#[test]
+fn handling_is_the_same_at_any_tick_rate() {
+    let input = Input {
+        right: true,
+        ..Input::default()
+    };
+    let (slow, _) = distance_moved(input, 1.0, 30.0);
+    let (fast, _) = distance_moved(input, 1.0, 240.0);
+    assert!((slow - fast).abs() / fast < 0.05, "{} vs {}", slow, fast);
+}
+
+#[test]
+fn diagonals_are_no_faster_than_straight_lines() {
+    let input = Input {
+        right: true,
+        down: true,
+        ..Input::default()
+    };
+    let mut state = test_state();
+    for _ in 0..120 {
+        state.tick(&input);
+        let speed = (state.player().speed_x.powi(2) + state.player().speed_y.powi(2)).sqrt();
+        assert!(speed <= state.player().top_speed + 1e-3);
+    }
+}
+
+#[test]
+fn the_player_stays_inside_the_playfield() {
+    let input = Input {
+        left: true,
+        up: true,
+        ..Input::default()
+    };
+    let mut state = test_state();
+    state.playfield = Playfield {
+        left: 10.0,
+        top: 20.0,
+        right: 500.0,
+        bottom: 400.0,
+    };
+    for _ in 0..300 {
+        state.tick(&input);
+    }
+    assert_eq!(state.player().shape.center_x, 10.0);
+    assert_eq!(state.player().shape.center_y, 20.0);
+    assert_eq!(state.player().speed_x, 0.0);
+}
@@ -0,0 +1,190 @@
+mod common;
+
+use common::test_state;
+use game::patterns::{Movement, Pattern};
+use game::stage::parse_stage;
+use game::{Entity, EntityType, Input, ModelId, ShipDraw, WeaponType};
+
+const DT: f32 = 1.0 / 60.0;
+
+//an enemy to try patterns on, away from everything else
+fn enemy_at(x: f32, y: f32) -> Entity {
+    let mut state = test_state();
+    let hitbox = state.models[1].aabb;
+    let handle = state.create_entity(
+        EntityType::Enemy,
+        WeaponType::None,
+        1,
+        ShipDraw::new(ModelId(0), x, y),
+        hitbox,
+        0.0,
+        0.0,
+        0.0,
+    );
+    state.entities.get(handle).unwrap().clone()
+}
+
+//follow a pattern from one age to another, the same way the game does every tick
+fn follow(pattern: &mut Pattern, e: &mut Entity, from: f32, to: f32, dt: f32) {
+    let start = (from / dt).round() as u32;
+    for i in start..(to / dt).round() as u32 {
+        pattern.update(e, &[], i as f32 * dt, dt);
+        e.integrate(dt);
+    }
+}
+
+fn position(e: &Entity) -> (f32, f32) {
+    (e.shape.center_x, e.shape.center_y)
+}
+
+fn close(a: (f32, f32), b: (f32, f32), tolerance: f32) -> bool {
+    (a.0 - b.0).abs() < tolerance && (a.1 - b.1).abs() < tolerance
+}
+
+#[test]
+fn sine_weaves_out_by_its_size() {
+    let mut pattern = Pattern::new(Movement::SineWeave, (0.0, 1.0), 60.0);
+    let mut e = enemy_at(500.0, 100.0);
+    //a quarter of the way through the period it is as far out to the side as it goes
+    follow(&mut pattern, &mut e, 0.0, 0.5, DT);
+    let (x, y) = position(&e);
+    assert!(
+        (x - 500.0).abs() > 79.0 && (x - 500.0).abs() < 81.0,
+        "{}",
+        x
+    );
+    assert!((y - 130.0).abs() < 0.1, "{}", y);
+    //and back in line after half of it
+    follow(&mut pattern, &mut e, 0.5, 1.0, DT);
+    assert!((e.shape.center_x - 500.0).abs() < 1.0);
+}
+
+#[test]
+fn loops_come_back_around() {
+    for movement in [Movement::Circle, Movement::FigureEight] {
+        let mut pattern = Pattern::new(movement, (0.0, 1.0), 0.0);
+        let mut e = enemy_at(500.0, 300.0);
+        //a figure eight crosses its start halfway around, so check a quarter of the way
+        follow(&mut pattern, &mut e, 0.0, 0.5, DT);
+        assert!(!close(position(&e), (500.0, 300.0), 10.0), "{:?}", movement);
+        follow(&mut pattern, &mut e, 0.5, 2.0, DT);
+        assert!(
+            close(position(&e), (500.0, 300.0), 0.5),
+            "{:?} ended up at {:?}",
+            movement,
+            position(&e)
+        );
+    }
+}
+
+#[test]
+fn dives_wait_and_retreat() {
+    let mut pattern = Pattern::new(Movement::DiveAndRetreat, (0.0, 1.0), 100.0);
+    pattern.period = 1.0;
+    let mut e = enemy_at(500.0, 0.0);
+    follow(&mut pattern, &mut e, 0.0, 1.0, DT);
+    assert!(
+        close(position(&e), (500.0, 100.0), 0.5),
+        "{:?}",
+        position(&e)
+    );
+    //waiting
+    pattern.update(&mut e, &[], 1.5, DT);
+    assert_eq!((e.speed_x, e.speed_y), (0.0, 0.0));
+    //going back out the way it came
+    pattern.update(&mut e, &[], 2.5, DT);
+    assert_eq!((e.speed_x, e.speed_y), (0.0, -100.0));
+}
+
+#[test]
+fn hovering_enemies_stop_and_stay_put() {
+    let mut pattern = Pattern::new(Movement::StopAndHover, (1.0, 0.0), 100.0);
+    pattern.size = 20.0;
+    let mut e = enemy_at(0.0, 300.0);
+    follow(&mut pattern, &mut e, 0.0, 2.0, DT);
+    assert!(
+        close(position(&e), (200.0, 300.0), 0.5),
+        "{:?}",
+        position(&e)
+    );
+    //it bobs across its heading for as long as it likes without drifting off
+    for second in 2..12 {
+        follow(&mut pattern, &mut e, second as f32, second as f32 + 1.0, DT);
+        let (x, y) = position(&e);
+        assert!((x - 200.0).abs() < 0.5, "{}", x);
+        assert!((y - 300.0).abs() <= 20.5, "{}", y);
+    }
+    assert_eq!(pattern.anchor.map(|(x, _)| x.round()), Some(200.0));
+}
+
+#[test]
+fn patterns_follow_the_same_path_at_any_tick_rate() {
+    for movement in [
+        Movement::SineWeave,
+        Movement::Circle,
+        Movement::FigureEight,
+        Movement::DiveAndRetreat,
+    ] {
+        let mut ends = Vec::new();
+        for dt in [1.0 / 30.0, 1.0 / 60.0, 1.0 / 240.0] {
+            let mut pattern = Pattern::new(movement, (0.6, 0.8), 50.0);
+            let mut e = enemy_at(500.0, 300.0);
+            follow(&mut pattern, &mut e, 0.0, 3.0, dt);
+            ends.push(position(&e));
+        }
+        for end in &ends[1..] {
+            assert!(close(ends[0], *end, 2.0), "{:?}: {:?}", movement, ends);
+        }
+    }
+}
+
+#[test]
+fn every_enemy_in_a_wave_runs_its_pattern() {
+    let mut state = test_state();
+    state.stage = parse_stage(
+        r#"
+        [[wave]]
+        time = 0.0
+        enemy = "grunt"
+        count = 3
+        entry = [500.0, 100.0]
+        pattern = "sine"
+        speed = 60.0
+        size = 40.0
+        period = 1.0
+        "#,
+        &state.enemies,
+    )
+    .unwrap();
+    state.tick(&Input::default());
+    let enemies: Vec<_> = state
+        .entities
+        .values()
+        .filter(|e| e.etype == EntityType::Enemy)
+        .collect();
+    assert_eq!(enemies.len(), 3);
+    for e in enemies {
+        let pattern = e.pattern.as_ref().unwrap();
+        assert_eq!(pattern.movement, Movement::SineWeave);
+        assert_eq!((pattern.size, pattern.period), (40.0, 1.0));
+        //waves at a point go down the screen
+        assert_eq!(pattern.heading, (0.0, 1.0));
+    }
+    let start: Vec<f32> = state
+        .entities
+        .values()
+        .filter(|e| e.etype == EntityType::Enemy)
+        .map(|e| e.shape.center_y)
+        .collect();
+    for _ in 0..60 {
+        state.tick(&Input::default());
+    }
+    for (e, y) in state
+        .entities
+        .values()
+        .filter(|e| e.etype == EntityType::Enemy)
+        .zip(start)
+    {
+        assert!((e.shape.center_y - y - 60.0).abs() < 2.0);
+    }
+}
@@ -0,0 +1,182 @@
+mod common;
+
+use common::{count, equip, test_state};
+use game::enemy::parse_enemies;
+use game::weapon::find_weapon;
+use game::{DeathCause, EntityHandle, EntityType, Input, ModelId, PickupKind, State};
+
+//a weapon that upgrades into more streams and faster fire, another to swap to,
+//and enemies that always or never drop pickups
+fn setup(state: &mut State) {
+    let weapons = r#"
+        [[weapon]]
+        name = "basic"
+        model = "triangle"
+        speed = 600.0
+        cooldown = 0.2
+        max_level = 2
+        level_count = 1
+        level_spread = 10.0
+        level_cooldown = 0.5
+
+        [[weapon]]
+        name = "other"
+        model = "triangle"
+        speed = 300.0
+        cooldown = 0.5
+    "#;
+    equip(state, weapons, "basic");
+    let enemies = r#"
+        [[enemy]]
+        name = "generous"
+        model = "triangle"
+        health = 1
+        drop_chance = 1.0
+        drops = ["upgrade"]
+
+        [[enemy]]
+        name = "stingy"
+        model = "triangle"
+        health = 1
+    "#;
+    state.enemies = parse_enemies(enemies, &state.models, &state.weapons).unwrap();
+}
+
+//put a pickup right on top of the player
+fn pickup_on_player(state: &mut State, kind: PickupKind) -> EntityHandle {
+    let (x, y) = (state.player().shape.center_x, state.player().shape.center_y);
+    state.spawn_pickup(kind, ModelId(0), x, y)
+}
+
+#[test]
+fn destroyed_enemies_drop_pickups_by_their_archetype() {
+    let mut state = test_state();
+    setup(&mut state);
+    let generous = state.spawn_archetype(0, 300.0, 300.0);
+    let stingy = state.spawn_archetype(1, 600.0, 300.0);
+    for enemy in [generous, stingy] {
+        let e = state.entities.get_mut(enemy).unwrap();
+        e.damage(1, None);
+    }
+    state.tick(&Input::default());
+
+    assert_eq!(count(&state, EntityType::Pickup), 1);
+    let pickup = state
+        .entities
+        .values()
+        .find(|e| e.etype == EntityType::Pickup)
+        .unwrap();
+    assert_eq!(pickup.pickup, Some(PickupKind::Upgrade));
+    assert_eq!(pickup.shape.center_x, 300.0);
+}
+
+#[test]
+fn enemies_that_leave_another_way_drop_nothing() {
+    let mut state = test_state();
+    setup(&mut state);
+    let enemy = state.spawn_archetype(0, 300.0, 300.0);
+    state.despawn(enemy);
+    state.tick(&Input::default());
+    assert_eq!(count(&state, EntityType::Pickup), 0);
+}
+
+#[test]
+fn upgrades_add_streams_and_fire_faster_up_to_the_top_level() {
+    let mut state = test_state();
+    setup(&mut state);
+    let health = state.player().health;
+    let pickup = pickup_on_player(&mut state, PickupKind::Upgrade);
+    state.tick(&Input::default());
+
+    assert_eq!(state.player().weapon_level, 1);
+    assert_eq!(state.player().health, health);
+    let death = state.deaths.iter().find(|d| d.handle == pickup).unwrap();
+    assert_eq!(death.cause, DeathCause::Collected);
+
+    assert_eq!(state.bullet(state.p1).len(), 2);
+    assert_eq!(state.weapon(state.p1).unwrap().at_level(1).cooldown, 0.1);
+
+    for _ in 0..3 {
+        pickup_on_player(&mut state, PickupKind::Upgrade);
+        state.tick(&Input::default());
+    }
+    assert_eq!(state.player().weapon_level, 2);
+    assert_eq!(state.bullet(state.p1).len(), 3);
+}
+
+#[test]
+fn weapon_pickups_swap_the_players_weapon() {
+    let mut state = test_state();
+    setup(&mut state);
+    let other = find_weapon(&state.weapons, "other").unwrap();
+    pickup_on_player(&mut state, PickupKind::Upgrade);
+    state.tick(&Input::default());
+    pickup_on_player(&mut state, PickupKind::Weapon(other));
+    state.tick(&Input::default());
+
+    assert_eq!(state.weapon(state.p1).unwrap().name, "other");
+    //the new weapon can't be upgraded, so the level doesn't carry over
+    assert_eq!(state.player().weapon_level, 0);
+}
+
+#[test]
+fn dying_loses_half_the_weapon_levels() {
+    let mut state = test_state();
+    setup(&mut state);
+    state.weapons[0].max_level = 10;
+    state.player_mut().weapon_level = 5;
+    state.player_mut().damage(100, None);
+    state.tick(&Input::default());
+    assert_eq!(state.player().weapon_level, 2);
+}
+
+#[test]
+fn only_the_player_can_take_pickups() {
+    let mut state = test_state();
+    setup(&mut state);
+    let pickup = state.spawn_pickup(PickupKind::Upgrade, ModelId(0), 500.0, 300.0);
+    let enemy = state.spawn_archetype(1, 500.0, 300.0);
+    let shot = state.bullet(state.p1)[0];
+    let e = state.entities.get_mut(shot).unwrap();
+    e.shape.center_x = 500.0;
+    e.shape.center_y = 300.0;
+    e.speed_y = 0.0;
+    state.tick(&Input::default());
+
+    assert!(state.entities.get(pickup).unwrap().is_alive());
+    assert!(!state
+        .contacts
+        .iter()
+        .any(|&(a, b)| a == pickup || b == pickup));
+    assert!(!state.entities.contains(enemy));
+}
+
+#[test]
+fn pickups_use_the_model_their_archetype_names() {
+    let mut state = test_state();
+    //one model only, so nothing can lean on there being a second one
+    state.models.truncate(1);
+    let enemies = r#"
+        [[enemy]]
+        name = "generous"
+        model = "triangle"
+        health = 1
+        drop_chance = 1.0
+        drops = ["upgrade"]
+        pickup_model = "triangle"
+    "#;
+    state.enemies = parse_enemies(enemies, &state.models, &state.weapons).unwrap();
+    let enemy = state.spawn_archetype(0, 300.0, 300.0);
+    state.entities.get_mut(enemy).unwrap().damage(1, None);
+    state.tick(&Input::default());
+    let pickup = state
+        .entities
+        .values()
+        .find(|e| e.etype == EntityType::Pickup)
+        .unwrap();
+    assert_eq!(pickup.shape.model, ModelId(0));
+
+    let missing = enemies.replace("pickup_model = \"triangle\"", "pickup_model = \"star\"");
+    let error = parse_enemies(&missing, &state.models, &state.weapons).unwrap_err();
+    assert!(error.contains("star"), "{}", error);
+}
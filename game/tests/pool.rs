@@ -0,0 +1,82 @@
+use game::Pool;
+
+#[test]
+fn released_values_leave_no_gap() {
+    let mut pool = Pool::new();
+    let a = pool.acquire("a");
+    let b = pool.acquire("b");
+    let c = pool.acquire("c");
+
+    assert_eq!(pool.release(a), Some("a"));
+    assert_eq!(pool.len(), 2);
+    //the last value moved into the gap, and its handle still finds it
+    assert_eq!(pool.get(c), Some(&"c"));
+    assert_eq!(pool.get(b), Some(&"b"));
+    assert_eq!(pool.values().copied().collect::<Vec<_>>(), vec!["c", "b"]);
+}
+
+#[test]
+fn handles_to_released_values_go_stale() {
+    let mut pool = Pool::new();
+    let a = pool.acquire(1);
+    pool.release(a);
+    let b = pool.acquire(2);
+
+    //the slot is reused, but the old handle doesn't see the new value
+    assert_eq!(b.index, a.index);
+    assert!(!pool.contains(a));
+    assert_eq!(pool.get(a), None);
+    assert_eq!(pool.release(a), None);
+    assert_eq!(pool.get(b), Some(&2));
+}
+
+#[test]
+fn iterating_gives_the_right_handle_for_every_value() {
+    let mut pool = Pool::new();
+    let handles: Vec<_> = (0..10).map(|i| pool.acquire(i)).collect();
+    for &h in handles.iter().step_by(3) {
+        pool.release(h);
+    }
+    for (h, value) in pool.iter_mut() {
+        *value *= 10;
+        assert_eq!(handles[h.index as usize], h);
+    }
+    for (h, value) in pool.iter() {
+        assert_eq!(*value, h.index * 10);
+    }
+    assert_eq!(pool.handles().len(), 6);
+}
+
+#[test]
+fn retain_checks_every_value_once() {
+    let mut pool = Pool::new();
+    for i in 0..10 {
+        pool.acquire(i);
+    }
+    let mut checked = 0;
+    pool.retain(|_, &v| {
+        checked += 1;
+        v % 2 == 0
+    });
+    assert_eq!(checked, 10);
+    let mut left: Vec<_> = pool.values().copied().collect();
+    left.sort();
+    assert_eq!(left, vec![0, 2, 4, 6, 8]);
+}
+
+#[test]
+fn churning_values_does_not_grow_the_pool() {
+    let mut pool = Pool::with_capacity(100);
+    let capacity = pool.capacity();
+    let mut live = Vec::new();
+    for i in 0..10000 {
+        live.push(pool.acquire(i));
+        if live.len() == 100 {
+            for h in live.drain(..50) {
+                pool.release(h);
+            }
+        }
+    }
+    assert!(pool.len() <= 100);
+    assert_eq!(pool.capacity(), capacity);
+}
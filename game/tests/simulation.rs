@@ -0,0 +1,86 @@
+mod common;
+
+use common::{count, test_state};
+use game::{EntityType, Input};
+
+#[test]
+fn firing_spawns_a_player_projectile() {
+    let mut state = test_state();
+    let input = Input {
+        fire: true,
+        ..Input::default()
+    };
+    state.update(&input, 1.0 / 60.0);
+    assert_eq!(count(&state, EntityType::PlayerProjectile), 1);
+}
+
+#[test]
+fn a_wave_spawns_after_ten_seconds() {
+    let mut state = test_state();
+    for _ in 0..590 {
+        state.update(&Input::default(), 1.0 / 60.0);
+    }
+    assert_eq!(count(&state, EntityType::Enemy), 0);
+    for _ in 0..20 {
+        state.update(&Input::default(), 1.0 / 60.0);
+    }
+    assert_eq!(count(&state, EntityType::Enemy), 5);
+}
+
+#[test]
+fn the_player_moves_with_input() {
+    let mut state = test_state();
+    let start_x = state.player().shape.center_x;
+    let input = Input {
+        right: true,
+        ..Input::default()
+    };
+    for _ in 0..10 {
+        state.update(&input, 1.0 / 60.0);
+    }
+    assert!(state.player().shape.center_x > start_x);
+}
+
+#[test]
+fn handles_to_despawned_entities_go_stale() {
+    let mut state = test_state();
+    let shot = state.bullet(state.p1)[0];
+    assert!(state.despawn(shot));
+    state.tick(&Input::default());
+    assert!(!state.despawn(shot));
+
+    //the freed slot is reused, but the old handle must not reach the new entity
+    let next = state.bullet(state.p1)[0];
+    assert_eq!(next.index, shot.index);
+    assert!(state.entities.get(shot).is_none());
+    assert!(state.entities.get(next).is_some());
+}
+
+#[test]
+fn holding_fire_shoots_at_the_weapon_rate_at_any_tick_rate() {
+    let input = Input {
+        fire: true,
+        ..Input::default()
+    };
+    for tick_rate in [30.0, 60.0, 144.0, 240.0] {
+        let mut state = test_state();
+        state.set_tick_rate(tick_rate);
+        let mut shots = 0;
+        for _ in 0..(tick_rate as u32) {
+            state.tick(&input);
+            shots += count(&state, EntityType::PlayerProjectile);
+            //clear the projectiles out so they are only counted once
+            let handles: Vec<_> = state
+                .entities
+                .iter()
+                .filter(|(_, e)| e.etype == EntityType::PlayerProjectile)
+                .map(|(h, _)| h)
+                .collect();
+            for h in handles {
+                state.entities.release(h);
+            }
+        }
+        let expected = (1.0 / state.weapon(state.p1).unwrap().cooldown).round() as usize;
+        assert_eq!(shots, expected, "at {} ticks per second", tick_rate);
+    }
+}
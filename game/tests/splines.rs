@@ -0,0 +1,186 @@
+mod common;
+
+use common::test_state;
+use game::spline::{Curve, PathEnd};
+use game::stage::parse_stage;
+use game::{EntityType, Input, Spline, State};
+
+const DT: f32 = 1.0 / 60.0;
+
+fn close(a: (f32, f32), b: (f32, f32), tolerance: f32) -> bool {
+    (a.0 - b.0).abs() < tolerance && (a.1 - b.1).abs() < tolerance
+}
+
+fn swoop(curve: Curve, end: PathEnd) -> Spline {
+    let points = match curve {
+        Curve::CatmullRom => vec![(0.0, 0.0), (100.0, 200.0), (300.0, 200.0), (400.0, 0.0)],
+        Curve::Bezier => vec![(0.0, 0.0), (0.0, 200.0), (400.0, 200.0), (400.0, 0.0)],
+    };
+    Spline::new("swoop", curve, points, end).unwrap()
+}
+
+fn enemy(state: &State) -> (f32, f32) {
+    let e = state
+        .entities
+        .values()
+        .find(|e| e.etype == EntityType::Enemy)
+        .unwrap();
+    (e.shape.center_x, e.shape.center_y)
+}
+
+#[test]
+fn catmull_rom_paths_go_through_every_point() {
+    let path = swoop(Curve::CatmullRom, PathEnd::Exit);
+    assert_eq!(path.start(), (0.0, 0.0));
+    assert!(close(path.at_distance(path.length()), (400.0, 0.0), 0.01));
+    for point in [(100.0, 200.0), (300.0, 200.0)] {
+        let passes = (0..1000)
+            .map(|i| path.at_distance(path.length() * i as f32 / 1000.0))
+            .any(|p| close(p, point, 1.0));
+        assert!(passes, "{:?}", point);
+    }
+}
+
+#[test]
+fn bezier_paths_are_pulled_toward_their_control_points() {
+    let path = swoop(Curve::Bezier, PathEnd::Exit);
+    assert!(close(path.at_distance(path.length()), (400.0, 0.0), 0.01));
+    //halfway along, a symmetric curve is at 3/4 of the way to its control points
+    assert!(close(
+        path.at_distance(path.length() / 2.0),
+        (200.0, 150.0),
+        1.0
+    ));
+    //a straight one is exactly as long as the line
+    let line = Spline::new(
+        "line",
+        Curve::Bezier,
+        vec![(0.0, 0.0), (100.0, 0.0), (200.0, 0.0), (300.0, 0.0)],
+        PathEnd::Exit,
+    )
+    .unwrap();
+    assert!((line.length() - 300.0).abs() < 0.01);
+}
+
+#[test]
+fn paths_end_by_leaving_or_looping() {
+    //exiting paths keep going the way they were heading at the end, straight up for this one
+    let path = swoop(Curve::Bezier, PathEnd::Exit);
+    let end = path.at_distance(path.length() + 100.0);
+    assert!(close(end, (400.0, -100.0), 0.5), "{:?}", end);
+
+    //looping ones close up and go around again
+    let looped = swoop(Curve::CatmullRom, PathEnd::Loop);
+    assert!(looped.length() > swoop(Curve::CatmullRom, PathEnd::Exit).length());
+    assert!(close(looped.at_distance(looped.length()), (0.0, 0.0), 0.01));
+    let d = 123.0;
+    assert!(close(
+        looped.at_distance(d),
+        looped.at_distance(d + looped.length() * 2.0),
+        0.1
+    ));
+}
+
+#[test]
+fn paths_with_the_wrong_points_are_refused() {
+    let bad = [
+        (Curve::CatmullRom, vec![(0.0, 0.0)], PathEnd::Exit),
+        (
+            Curve::Bezier,
+            vec![(0.0, 0.0), (1.0, 1.0), (2.0, 2.0)],
+            PathEnd::Exit,
+        ),
+        (
+            Curve::Bezier,
+            vec![(0.0, 0.0), (1.0, 1.0), (2.0, 2.0), (3.0, 3.0), (4.0, 4.0)],
+            PathEnd::Exit,
+        ),
+        (
+            Curve::Bezier,
+            vec![(0.0, 0.0), (1.0, 1.0), (2.0, 2.0), (3.0, 3.0)],
+            PathEnd::Loop,
+        ),
+    ];
+    for (curve, points, end) in bad {
+        assert!(Spline::new("bad", curve, points, end).is_err());
+    }
+}
+
+#[test]
+fn enemies_follow_paths_from_where_they_appear_at_a_steady_speed() {
+    let mut state = test_state();
+    state.stage = parse_stage(
+        r#"
+        [[path]]
+        name = "other"
+        points = [[0.0, 0.0], [-100.0, 50.0]]
+
+        [[path]]
+        name = "swoop"
+        curve = "bezier"
+        points = [[0.0, 0.0], [0.0, 200.0], [400.0, 200.0], [400.0, 0.0]]
+
+        [[wave]]
+        time = 0.0
+        enemy = "grunt"
+        entry = [100.0, 100.0]
+        path = "swoop"
+        speed = 120.0
+        "#,
+        &state.enemies,
+    )
+    .unwrap();
+    //waves point at their path in the stage rather than carrying a copy of it
+    assert_eq!(state.stage.waves[0].pattern.path, Some(1));
+    let path = state.stage.paths[1].clone();
+    state.tick(&Input::default());
+    let start = enemy(&state);
+    assert_eq!(start, (100.0, 100.0));
+
+    let mut last = start;
+    for tick in 1..=120 {
+        state.tick(&Input::default());
+        let now = enemy(&state);
+        let step = ((now.0 - last.0).powi(2) + (now.1 - last.1).powi(2)).sqrt();
+        assert!((step - 120.0 * DT).abs() < 0.1, "{}", step);
+        let (px, py) = path.at_distance(120.0 * DT * tick as f32);
+        assert!(close(now, (100.0 + px, 100.0 + py), 0.1), "{:?}", now);
+        last = now;
+    }
+}
+
+#[test]
+fn path_mistakes_point_at_their_line() {
+    let state = test_state();
+    let cases = [
+        //a wave following a path that isn't there
+        (
+            "[[wave]]\ntime = 1.0\nenemy = \"grunt\"\nentry = [0.0, 0.0]\npath = \"swoop\"\nspeed = 10.0\n",
+            "line 5",
+            "swoop",
+        ),
+        //a Bezier path without enough points
+        (
+            "[[path]]\nname = \"swoop\"\ncurve = \"bezier\"\npoints = [[0.0, 0.0], [1.0, 1.0]]\n\n[[wave]]\ntime = 1.0\nenemy = \"grunt\"\nentry = [0.0, 0.0]\n",
+            "line 2",
+            "Bezier",
+        ),
+        //two paths with the same name
+        (
+            "[[path]]\nname = \"swoop\"\npoints = [[0.0, 0.0], [1.0, 1.0]]\n\n[[path]]\nname = \"swoop\"\npoints = [[0.0, 0.0], [1.0, 1.0]]\n\n[[wave]]\ntime = 1.0\nenemy = \"grunt\"\nentry = [0.0, 0.0]\n",
+            "line 6",
+            "already",
+        ),
+        //a pattern and a path at once
+        (
+            "[[path]]\nname = \"swoop\"\npoints = [[0.0, 0.0], [1.0, 1.0]]\n\n[[wave]]\ntime = 1.0\nenemy = \"grunt\"\nentry = [0.0, 0.0]\npattern = \"sine\"\npath = \"swoop\"\nspeed = 10.0\n",
+            "line 10",
+            "not both",
+        ),
+    ];
+    for (text, line, mention) in cases {
+        let error = parse_stage(text, &state.enemies).unwrap_err();
+        assert!(error.starts_with(line), "{}", error);
+        assert!(error.contains(mention), "{}", error);
+    }
+}
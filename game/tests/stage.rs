@@ -0,0 +1,188 @@
+mod common;
+
+use common::{count, test_state};
+use game::stage::parse_stage;
+use game::{EntityType, Input, State};
+
+const DT: f32 = 1.0 / 60.0;
+
+fn run(state: &mut State, seconds: f32) {
+    for _ in 0..(seconds / DT).round() as u32 {
+        state.tick(&Input::default());
+    }
+}
+
+fn load(state: &mut State, text: &str) {
+    state.stage = parse_stage(text, &state.enemies).unwrap();
+}
+
+fn enemy_positions(state: &State) -> Vec<(f32, f32)> {
+    let mut positions: Vec<_> = state
+        .entities
+        .values()
+        .filter(|e| e.etype == EntityType::Enemy)
+        .map(|e| (e.shape.center_x, e.shape.center_y))
+        .collect();
+    positions.sort_by(|a, b| a.0.total_cmp(&b.0));
+    positions
+}
+
+#[test]
+fn waves_come_in_at_their_times_in_formation() {
+    let mut state = test_state();
+    //written out of order on purpose
+    load(
+        &mut state,
+        r#"
+        [[wave]]
+        time = 2.0
+        enemy = "grunt"
+        count = 2
+        entry = [500.0, 300.0]
+
+        [[wave]]
+        time = 1.0
+        enemy = "grunt"
+        count = 3
+        spacing = 100.0
+        entry = [500.0, 100.0]
+        "#,
+    );
+    run(&mut state, 0.9);
+    assert_eq!(count(&state, EntityType::Enemy), 0);
+    run(&mut state, 0.2);
+    assert_eq!(
+        enemy_positions(&state),
+        vec![(400.0, 100.0), (500.0, 100.0), (600.0, 100.0)]
+    );
+    run(&mut state, 1.0);
+    assert_eq!(count(&state, EntityType::Enemy), 5);
+}
+
+#[test]
+fn the_timeline_starts_over_when_the_stage_repeats() {
+    let mut state = test_state();
+    load(
+        &mut state,
+        r#"
+        repeat = 3.0
+
+        [[wave]]
+        time = 0.0
+        enemy = "grunt"
+        entry = [500.0, 100.0]
+        "#,
+    );
+    run(&mut state, 0.5);
+    assert_eq!(count(&state, EntityType::Enemy), 1);
+    run(&mut state, 6.0);
+    assert_eq!(count(&state, EntityType::Enemy), 3);
+}
+
+#[test]
+fn stages_that_dont_repeat_end() {
+    let mut state = test_state();
+    load(
+        &mut state,
+        r#"
+        [[wave]]
+        time = 0.0
+        enemy = "grunt"
+        entry = [500.0, 100.0]
+        "#,
+    );
+    run(&mut state, 30.0);
+    assert_eq!(count(&state, EntityType::Enemy), 1);
+}
+
+#[test]
+fn enemies_move_by_their_wave_pattern() {
+    let mut state = test_state();
+    load(
+        &mut state,
+        r#"
+        [[wave]]
+        time = 0.0
+        enemy = "grunt"
+        entry = [100.0, 100.0]
+        pattern = "left_to_right"
+        speed = 120.0
+        "#,
+    );
+    run(&mut state, 1.0);
+    let (x, y) = enemy_positions(&state)[0];
+    assert!((x - 220.0).abs() < 3.0, "{}", x);
+    assert_eq!(y, 100.0);
+}
+
+#[test]
+fn mistakes_point_at_their_line() {
+    let state = test_state();
+    let cases = [
+        //an enemy that isn't in the enemies file
+        (
+            "[[wave]]\ntime = 1.0\nenemy = \"ghost\"\nentry = [0.0, 0.0]\n",
+            "line 3",
+            "ghost",
+        ),
+        //a movement pattern that doesn't exist
+        (
+            "[[wave]]\ntime = 1.0\nenemy = \"grunt\"\nentry = [0.0, 0.0]\n\n[[wave]]\ntime = 2.0\nenemy = \"grunt\"\nentry = [0.0, 0.0]\npattern = \"zigzag\"\n",
+            "line 10",
+            "zigzag",
+        ),
+        (
+            "[[wave]]\ntime = -1.0\nenemy = \"grunt\"\nentry = [0.0, 0.0]\n",
+            "line 2",
+            "negative",
+        ),
+        (
+            "repeat = 5.0\n[[wave]]\ntime = 1.0\nenemy = \"grunt\"\ncount = 0\nentry = [0.0, 0.0]\n",
+            "line 5",
+            "count",
+        ),
+        (
+            "repeat = 5.0\n[[wave]]\ntime = 6.0\nenemy = \"grunt\"\nentry = [0.0, 0.0]\n",
+            "line 3",
+            "repeats",
+        ),
+        //each wave field's own mistakes point at that field
+        (
+            "[[wave]]\ntime = 1.0\nenemy = \"grunt\"\nentry = [0.0, 0.0]\nperiod = 0.0\n",
+            "line 5",
+            "period",
+        ),
+        (
+            "[[wave]]\ntime = 1.0\nenemy = \"grunt\"\nentry = [0.0, 0.0]\ncount = 3\nspacing = 0.0\n",
+            "line 6",
+            "spacing",
+        ),
+        (
+            "[[wave]]\ntime = 1.0\nenemy = \"grunt\"\nentry = [0.0, 0.0]\npattern = \"sine\"\nsize = -1.0\n",
+            "line 6",
+            "size",
+        ),
+        (
+            "[[wave]]\ntime = 1.0\nenemy = \"grunt\"\nfrom = \"top\"\nspeed = 0.0\n",
+            "line 5",
+            "speed",
+        ),
+        //an entry point so far out its enemies would be removed right away
+        (
+            "[[wave]]\ntime = 1.0\nenemy = \"grunt\"\nentry = [500.0, -400.0]\n",
+            "line 4",
+            "outside",
+        ),
+        //a field the game doesn't know about is caught by the parser, which points at it too
+        (
+            "[[wave]]\ntime = 1.0\nenemy = \"grunt\"\nentry = [0.0, 0.0]\nsped = 4.0\n",
+            "line 5",
+            "sped",
+        ),
+    ];
+    for (text, line, what) in cases {
+        let error = parse_stage(text, &state.enemies).unwrap_err();
+        assert!(error.contains(line), "{}", error);
+        assert!(error.contains(what), "{}", error);
+    }
+}
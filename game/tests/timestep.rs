@@ -0,0 +1,17 @@
+use game::FixedTimestep;
+
+#[test]
+fn short_frames_run_no_ticks_until_enough_time_builds_up() {
+    let mut clock = FixedTimestep::new(100.0);
+    assert_eq!(clock.advance(0.004), 0);
+    assert!((clock.alpha() - 0.4).abs() < 1e-4);
+    assert_eq!(clock.advance(0.007), 1);
+    assert!((clock.alpha() - 0.1).abs() < 1e-4);
+}
+
+#[test]
+fn long_frames_run_several_ticks_but_not_forever() {
+    let mut clock = FixedTimestep::new(100.0);
+    assert_eq!(clock.advance(0.035), 3);
+    assert_eq!(clock.advance(10.0), game::timestep::MAX_TICKS_PER_FRAME);
+}
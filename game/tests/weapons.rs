@@ -0,0 +1,306 @@
+mod common;
+
+use common::{count, equip, test_models, test_state};
+use game::enemy::{load_enemies, parse_enemies};
+use game::model::load_models;
+use game::stage::{load_stage, parse_stage};
+use game::weapon::{find_weapon, load_weapons, parse_weapons};
+use game::{EntityType, Input};
+use std::path::Path;
+
+#[test]
+fn spread_fans_projectiles_evenly_around_straight_ahead() {
+    let mut state = test_state();
+    equip(
+        &mut state,
+        r#"
+        [[weapon]]
+        name = "test"
+        model = "triangle"
+        count = 3
+        spread = 90.0
+        speed = 100.0
+        cooldown = 0.1
+        "#,
+        "test",
+    );
+    let shots = state.bullet(state.p1);
+    assert_eq!(shots.len(), 3);
+
+    let speeds: Vec<(f32, f32)> = shots
+        .iter()
+        .map(|&h| {
+            let e = state.entities.get(h).unwrap();
+            (e.speed_x, e.speed_y)
+        })
+        .collect();
+    //45 degrees either side of straight up, and one straight up
+    let side = 100.0 * std::f32::consts::FRAC_1_SQRT_2;
+    assert!((speeds[0].0 + side).abs() < 0.01 && (speeds[0].1 + side).abs() < 0.01);
+    assert!(speeds[1].0.abs() < 0.01 && (speeds[1].1 + 100.0).abs() < 0.01);
+    assert!((speeds[2].0 - side).abs() < 0.01 && (speeds[2].1 + side).abs() < 0.01);
+}
+
+#[test]
+fn projectiles_take_damage_and_punch_through_from_the_definition() {
+    let mut state = test_state();
+    equip(
+        &mut state,
+        r#"
+        [[weapon]]
+        name = "test"
+        model = "triangle"
+        speed = 100.0
+        damage = 4
+        cooldown = 0.1
+        punch_through = 3
+        "#,
+        "test",
+    );
+    let shot = state.bullet(state.p1)[0];
+    let e = state.entities.get(shot).unwrap();
+    assert_eq!(e.etype, EntityType::PlayerProjectile);
+    assert_eq!(e.collision_damage, 4);
+    assert_eq!(e.health, 3);
+}
+
+#[test]
+fn a_burst_fires_every_shot_from_one_trigger_pull() {
+    let mut state = test_state();
+    equip(
+        &mut state,
+        r#"
+        [[weapon]]
+        name = "test"
+        model = "triangle"
+        burst = 3
+        burst_interval = 0.1
+        speed = 100.0
+        cooldown = 1.0
+        "#,
+        "test",
+    );
+    let fire = Input {
+        fire: true,
+        ..Input::default()
+    };
+    state.tick(&fire);
+    assert_eq!(count(&state, EntityType::PlayerProjectile), 1);
+
+    //the rest of the burst comes out without holding fire, a tenth of a second apart
+    for _ in 0..7 {
+        state.tick(&Input::default());
+    }
+    assert_eq!(count(&state, EntityType::PlayerProjectile), 2);
+    for _ in 0..6 {
+        state.tick(&Input::default());
+    }
+    assert_eq!(count(&state, EntityType::PlayerProjectile), 3);
+    for _ in 0..30 {
+        state.tick(&Input::default());
+    }
+    assert_eq!(count(&state, EntityType::PlayerProjectile), 3);
+}
+
+#[test]
+fn bad_weapon_files_are_rejected() {
+    let models = test_models();
+    let missing_model = r#"
+        [[weapon]]
+        name = "test"
+        model = "nothing"
+        speed = 100.0
+        cooldown = 0.1
+    "#;
+    assert!(parse_weapons(missing_model, &models)
+        .unwrap_err()
+        .contains("nothing"));
+
+    let no_projectiles = r#"
+        [[weapon]]
+        name = "test"
+        model = "triangle"
+        count = 0
+        speed = 100.0
+        cooldown = 0.1
+    "#;
+    assert!(parse_weapons(no_projectiles, &models)
+        .unwrap_err()
+        .contains("count"));
+
+    //errors from the file itself say which line they are on
+    let misspelled =
+        "[[weapon]]\nname = \"test\"\nmodel = \"triangle\"\nsped = 100.0\ncooldown = 0.1\n";
+    let error = parse_weapons(misspelled, &models).unwrap_err();
+    assert!(error.contains("line 4"), "{}", error);
+}
+
+#[test]
+fn the_shipped_weapons_file_loads() {
+    let assets = Path::new(env!("CARGO_MANIFEST_DIR")).join("../assets");
+    let models = load_models(&assets.join("models")).unwrap();
+    let weapons = load_weapons(&assets.join("weapons.toml"), &models).unwrap();
+    assert!(find_weapon(&weapons, "player_basic").is_some());
+}
+
+//weapons for enemies to use, one of each aim
+const ENEMY_WEAPONS: &str = r#"
+    [[weapon]]
+    name = "enemy_aimed"
+    model = "triangle"
+    aim = "player"
+    speed = 100.0
+    cooldown = 1.0
+
+    [[weapon]]
+    name = "enemy_volley"
+    model = "triangle"
+    count = 2
+    spread = 20.0
+    speed = 100.0
+    cooldown = 1.0
+
+    [[weapon]]
+    name = "enemy_ring"
+    model = "triangle"
+    aim = "ring"
+    count = 8
+    speed = 100.0
+    cooldown = 1.0
+"#;
+
+//switch to the enemy weapons, with an enemy archetype using each one
+fn arm_enemies(state: &mut game::State) {
+    state.weapons = parse_weapons(ENEMY_WEAPONS, &state.models).unwrap();
+    let enemies = r#"
+        [[enemy]]
+        name = "aimed"
+        model = "triangle"
+        health = 5
+        weapon = "enemy_aimed"
+
+        [[enemy]]
+        name = "volley"
+        model = "triangle"
+        health = 5
+        weapon = "enemy_volley"
+
+        [[enemy]]
+        name = "ring"
+        model = "triangle"
+        health = 5
+        weapon = "enemy_ring"
+    "#;
+    state.enemies = parse_enemies(enemies, &state.models, &state.weapons).unwrap();
+}
+
+#[test]
+fn aimed_shots_head_for_the_player() {
+    let mut state = test_state();
+    arm_enemies(&mut state);
+    let enemy = state.spawn_archetype(0, 100.0, 100.0);
+    state.entities.get_mut(enemy).unwrap().shape.center_x = 300.0;
+    state.player_mut().shape.center_x = 100.0;
+    state.player_mut().shape.center_y = 300.0;
+
+    let shot = state.bullet(enemy)[0];
+    let e = state.entities.get(shot).unwrap();
+    assert_eq!(e.etype, EntityType::EnemyProjectile);
+    //the enemy is at (300, 100), so the player is down and to the left at 45 degrees
+    assert!(e.speed_x < 0.0 && e.speed_y > 0.0);
+    assert!((e.speed_x + e.speed_y).abs() < 0.01);
+}
+
+#[test]
+fn rings_surround_the_shooter() {
+    let mut state = test_state();
+    arm_enemies(&mut state);
+    let ring = state.spawn_archetype(2, 100.0, 100.0);
+
+    let shots = state.bullet(ring);
+    assert_eq!(shots.len(), 8);
+    let (mut total_x, mut total_y) = (0.0, 0.0);
+    for h in shots {
+        let e = state.entities.get(h).unwrap();
+        let speed = (e.speed_x * e.speed_x + e.speed_y * e.speed_y).sqrt();
+        assert!((speed - 100.0).abs() < 0.01);
+        total_x += e.speed_x;
+        total_y += e.speed_y;
+    }
+    //evenly spaced all the way around, so they cancel out
+    assert!(total_x.abs() < 0.01 && total_y.abs() < 0.01);
+}
+
+#[test]
+fn enemies_fire_on_their_own_timers() {
+    let mut state = test_state();
+    arm_enemies(&mut state);
+    //keep the player out of the way so no shots hit them
+    state.player_mut().shape.center_x = 900.0;
+    state.player_mut().shape.center_y = 550.0;
+    let first = state.spawn_archetype(0, 100.0, 100.0);
+    for _ in 0..30 {
+        state.tick(&Input::default());
+    }
+    let second = state.spawn_archetype(1, 100.0, 100.0);
+    assert!(state.entities.get(first).unwrap().wtype != state.entities.get(second).unwrap().wtype);
+
+    //neither fires right as it spawns, then each fires once a second from when it appeared
+    let mut shots = Vec::new();
+    for _ in 0..120 {
+        state.tick(&Input::default());
+        shots.push(count(&state, EntityType::EnemyProjectile));
+    }
+    assert_eq!(shots[25], 0);
+    assert_eq!(shots[35], 1);
+    assert_eq!(shots[65], 1 + 2);
+    assert_eq!(shots[95], 1 + 2 + 1);
+}
+
+#[test]
+fn enemies_coming_in_from_an_edge_hold_their_fire_until_they_are_in() {
+    let mut state = test_state();
+    arm_enemies(&mut state);
+    state.player_mut().shape.center_x = 900.0;
+    state.player_mut().shape.center_y = 550.0;
+    //slow enough that the weapon is ready long before the enemy gets in
+    let text = r#"
+        [[wave]]
+        time = 0.0
+        enemy = "aimed"
+        from = "top"
+        at = 100.0
+        speed = 20.0
+    "#;
+    state.stage = parse_stage(text, &state.enemies).unwrap();
+    let mut entered = false;
+    for _ in 0..60 * 8 {
+        state.tick(&Input::default());
+        let enemy = state
+            .entities
+            .values()
+            .find(|e| e.etype == EntityType::Enemy);
+        if let Some(enemy) = enemy {
+            if !enemy.entered {
+                assert_eq!(count(&state, EntityType::EnemyProjectile), 0);
+            }
+            entered |= enemy.entered;
+        }
+    }
+    assert!(entered);
+    assert!(count(&state, EntityType::EnemyProjectile) > 0);
+}
+
+#[test]
+fn shipped_enemies_are_armed() {
+    let assets = Path::new(env!("CARGO_MANIFEST_DIR")).join("../assets");
+    let models = load_models(&assets.join("models")).unwrap();
+    let weapons = load_weapons(&assets.join("weapons.toml"), &models).unwrap();
+    let enemies = load_enemies(&assets.join("enemies.toml"), &models, &weapons).unwrap();
+    let stage = load_stage(&assets.join("stage.toml"), &enemies).unwrap();
+    let mut state = game::State::new(models, weapons, enemies, stage).unwrap();
+    for archetype in 0..state.enemies.len() {
+        let enemy = state.spawn_archetype(archetype, 100.0, 100.0);
+        assert!(state.weapon(enemy).is_some());
+    }
+}
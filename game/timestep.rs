@@ -0,0 +1,66 @@
+//fixed timestep clock
+//frames can take any amount of time, but the game always moves forward in ticks of the same length
+//leftover time is carried over to the next frame, and is used to blend drawing between the last two ticks
+
+//ticks per second the game runs at unless told otherwise
+pub const DEFAULT_TICK_RATE: f32 = 60.0;
+
+//most ticks that will be run in a single frame
+//if a frame takes longer than this the game slows down instead of freezing trying to catch up
+pub const MAX_TICKS_PER_FRAME: u32 = 8;
+
+#[derive(Clone, Copy, Debug)]
+pub struct FixedTimestep {
+    //length of a single tick in seconds
+    dt: f32,
+    //time that has passed but has not been simulated yet
+    accumulator: f32,
+}
+
+impl FixedTimestep {
+    //create a clock that ticks a given number of times per second
+    pub fn new(tick_rate: f32) -> FixedTimestep {
+        assert!(tick_rate > 0.0, "tick rate must be positive");
+        FixedTimestep {
+            dt: 1.0 / tick_rate,
+            accumulator: 0.0,
+        }
+    }
+
+    //length of a single tick in seconds
+    pub fn dt(&self) -> f32 {
+        self.dt
+    }
+
+    pub fn tick_rate(&self) -> f32 {
+        1.0 / self.dt
+    }
+
+    //add a frame's worth of time and return how many ticks should be run for it
+    pub fn advance(&mut self, frame_delta: f32) -> u32 {
+        self.accumulator += frame_delta.max(0.0);
+        let mut ticks = 0;
+        while self.accumulator >= self.dt {
+            self.accumulator -= self.dt;
+            ticks += 1;
+            if ticks == MAX_TICKS_PER_FRAME {
+                //drop whatever is left rather than falling further behind
+                self.accumulator = self.accumulator.min(self.dt);
+                break;
+            }
+        }
+        ticks
+    }
+
+    //how far between the last tick and the next one the current frame is, from 0 to 1
+    //used to blend positions when drawing
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.dt).clamp(0.0, 1.0)
+    }
+}
+
+impl Default for FixedTimestep {
+    fn default() -> FixedTimestep {
+        FixedTimestep::new(DEFAULT_TICK_RATE)
+    }
+}
@@ -0,0 +1,311 @@
+//weapon definitions, loaded from a data file
+//each weapon describes the pattern of projectiles it fires, so new weapons only need a new entry in the file
+
+use crate::entity::WeaponType;
+use crate::model::{find_model, ModelData, ModelId};
+use serde::Deserialize;
+use std::path::Path;
+
+//which way a weapon points its shots
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Aim {
+    //the way the shooter faces: up the screen for the player, down it for everything else
+    #[default]
+    Forward,
+    //straight at the player, wherever they are when the shot is fired
+    Player,
+    //evenly all the way around the shooter. spread and direction are ignored
+    Ring,
+}
+
+//what a weapon fires
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WeaponKind {
+    //projectiles, in the pattern set by count, spread, burst and aim
+    #[default]
+    Projectile,
+    //a beam that hits the first thing in its way for as long as fire is held
+    Beam,
+}
+
+//the parts of a weapon that upgrades change, worked out for one level
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WeaponLevel {
+    pub count: u32,
+    pub spread: f32,
+    pub cooldown: f32,
+}
+
+//refers to one weapon in the list of loaded weapons
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WeaponId(pub usize);
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WeaponDef {
+    //what the weapon is called in the data file
+    pub name: String,
+    //projectiles or a beam
+    #[serde(default)]
+    pub kind: WeaponKind,
+    //name of the model the projectiles use, or that is stretched out to draw the beam
+    pub model: String,
+    //projectiles fired at once
+    #[serde(default = "one")]
+    pub count: u32,
+    //angle in degrees the projectiles are fanned out over. 0 fires them all straight ahead
+    #[serde(default)]
+    pub spread: f32,
+    //what the shots are pointed at
+    #[serde(default)]
+    pub aim: Aim,
+    //angle in degrees to turn the whole shot away from where it is aimed. positive turns it clockwise
+    #[serde(default)]
+    pub direction: f32,
+    //shots fired each time the trigger is pulled
+    #[serde(default = "one")]
+    pub burst: u32,
+    //seconds between the shots in a burst
+    #[serde(default)]
+    pub burst_interval: f32,
+    //projectile speed, in playfield units per second
+    #[serde(default)]
+    pub speed: f32,
+    //damage each projectile does to what it hits
+    #[serde(default = "one_i32")]
+    pub damage: i32,
+    //seconds from one trigger pull until the next is allowed
+    #[serde(default)]
+    pub cooldown: f32,
+    //how many things each projectile can hit before it is used up
+    #[serde(default = "one_i32")]
+    pub punch_through: i32,
+    //degrees per second the projectiles can turn to chase the nearest target. 0 fires them straight
+    #[serde(default)]
+    pub turn_rate: f32,
+    //seconds each projectile lasts. 0 keeps it until it leaves the playfield or hits something
+    #[serde(default)]
+    pub lifetime: f32,
+    //damage a beam does each second to what it is touching
+    #[serde(default)]
+    pub dps: f32,
+    //how far a beam reaches, in playfield units
+    #[serde(default)]
+    pub range: f32,
+    //how wide a beam is, in playfield units
+    #[serde(default)]
+    pub width: f32,
+    //how big the projectiles are compared to their model, for both drawing and hitting
+    #[serde(default = "one_f32")]
+    pub size: f32,
+    //seconds the fire key has to be held for each charge level. 0 means the weapon can't be charged
+    #[serde(default)]
+    pub charge_time: f32,
+    //names of the weapons fired when the key is let go after charging, one for each charge level
+    #[serde(default)]
+    pub charged: Vec<String>,
+    //how many times the weapon can be upgraded by pickups
+    #[serde(default)]
+    pub max_level: u32,
+    //projectiles added to each shot per level
+    #[serde(default)]
+    pub level_count: u32,
+    //degrees added to the spread per level, so the extra projectiles fan out
+    #[serde(default)]
+    pub level_spread: f32,
+    //what the cooldown is multiplied by per level. below 1 fires faster
+    #[serde(default = "one_f32")]
+    pub level_cooldown: f32,
+    //trigger pulls before the weapon has to reload. 0 never runs out
+    #[serde(default)]
+    pub ammo: u32,
+    //seconds it takes to reload once the ammo runs out
+    #[serde(default)]
+    pub reload_time: f32,
+    //heat added each trigger pull, where 1 is overheated. for beams, heat added per second of firing. 0 never heats up
+    #[serde(default)]
+    pub heat_per_shot: f32,
+    //heat lost each second while the weapon isn't overheated
+    #[serde(default)]
+    pub cooling: f32,
+    //seconds the weapon can't fire after overheating. the heat is gone once it's over
+    #[serde(default)]
+    pub overheat_time: f32,
+    //the model, found by its name. filled in when loading
+    #[serde(skip)]
+    pub model_id: ModelId,
+    //the charged weapons, by where they are in the weapon list. filled in when loading
+    #[serde(skip)]
+    pub charged_ids: Vec<WeaponId>,
+}
+
+fn one() -> u32 {
+    1
+}
+
+fn one_i32() -> i32 {
+    1
+}
+
+fn one_f32() -> f32 {
+    1.0
+}
+
+//the layout of the weapons file: a list of [[weapon]] tables
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WeaponFile {
+    weapon: Vec<WeaponDef>,
+}
+
+impl WeaponDef {
+    //the weapon type for an entity holding this weapon, which is the id'th in the list
+    pub fn equip(&self, id: WeaponId) -> WeaponType {
+        match self.kind {
+            WeaponKind::Projectile => WeaponType::Projectile(id),
+            WeaponKind::Beam => WeaponType::Beam(id),
+        }
+    }
+
+    //the weapon's count, spread and cooldown after being upgraded to a level
+    //levels past max_level count as max_level
+    pub fn at_level(&self, level: u32) -> WeaponLevel {
+        let level = level.min(self.max_level);
+        WeaponLevel {
+            count: self.count + self.level_count * level,
+            spread: self.spread + self.level_spread * level as f32,
+            cooldown: self.cooldown * self.level_cooldown.powi(level as i32),
+        }
+    }
+
+    //true if holding the fire key charges up a bigger shot
+    pub fn can_charge(&self) -> bool {
+        self.charge_time > 0.0 && !self.charged_ids.is_empty()
+    }
+
+    //the charge level reached after holding the fire key for some time
+    pub fn charge_level(&self, held: f32) -> u32 {
+        if !self.can_charge() {
+            return 0;
+        }
+        ((held / self.charge_time).floor() as u32).min(self.charged_ids.len() as u32)
+    }
+
+    //the direction of each projectile in one shot at a level, as angles in radians away from where it is aimed
+    pub fn angles(&self, level: &WeaponLevel) -> impl Iterator<Item = f32> {
+        let count = level.count;
+        let spread = level.spread.to_radians();
+        let direction = self.direction.to_radians();
+        let (first, step) = if self.aim == Aim::Ring {
+            (0.0, std::f32::consts::TAU / count as f32)
+        } else if count <= 1 {
+            (direction, 0.0)
+        } else {
+            (direction - spread / 2.0, spread / (count - 1) as f32)
+        };
+        (0..count).map(move |i| first + step * i as f32)
+    }
+}
+
+//read weapons from the text of a weapons file, matching each one's model against the loaded models
+pub fn parse_weapons(text: &str, models: &[ModelData]) -> Result<Vec<WeaponDef>, String> {
+    let file: WeaponFile = match toml::from_str(text) {
+        Ok(f) => f,
+        Err(error) => return Err(error.to_string()),
+    };
+    let mut weapons = file.weapon;
+    for i in 0..weapons.len() {
+        let w = &weapons[i];
+        if weapons[..i].iter().any(|other| other.name == w.name) {
+            return Err(format!("weapon \"{}\" is defined twice", w.name));
+        }
+        let problem = if w.count == 0 {
+            Some("count must be at least 1")
+        } else if w.burst == 0 {
+            Some("burst must be at least 1")
+        } else if w.punch_through < 1 {
+            Some("punch_through must be at least 1")
+        } else if w.kind == WeaponKind::Projectile && w.speed <= 0.0 {
+            Some("speed must be positive")
+        } else if w.kind == WeaponKind::Beam && (w.dps <= 0.0 || w.range <= 0.0 || w.width <= 0.0) {
+            Some("beams need a positive dps, range and width")
+        } else if w.cooldown < 0.0 || w.burst_interval < 0.0 {
+            Some("cooldown and burst_interval can't be negative")
+        } else if w.turn_rate < 0.0 || w.lifetime < 0.0 {
+            Some("turn_rate and lifetime can't be negative")
+        } else if w.size <= 0.0 {
+            Some("size must be positive")
+        } else if w.charge_time < 0.0 {
+            Some("charge_time can't be negative")
+        } else if w.kind == WeaponKind::Beam && (w.charge_time > 0.0 || !w.charged.is_empty()) {
+            Some("beams can't be charged")
+        } else if w.level_cooldown <= 0.0 || w.level_spread < 0.0 {
+            Some("level_cooldown must be positive, and level_spread can't be negative")
+        } else if w.ammo > 0 && (w.kind == WeaponKind::Beam || w.reload_time <= 0.0) {
+            Some("ammo needs a positive reload_time, and beams can't use ammo")
+        } else if w.heat_per_shot < 0.0 || w.cooling < 0.0 {
+            Some("heat_per_shot and cooling can't be negative")
+        } else if w.heat_per_shot > 0.0 && w.overheat_time <= 0.0 {
+            Some("heat_per_shot needs a positive overheat_time")
+        } else {
+            None
+        };
+        if let Some(problem) = problem {
+            return Err(format!("weapon \"{}\": {}", w.name, problem));
+        }
+        weapons[i].model_id = match find_model(models, &w.model) {
+            Some(id) => id,
+            None => {
+                return Err(format!(
+                    "weapon \"{}\" uses model \"{}\", which was not loaded",
+                    w.name, w.model
+                ))
+            }
+        };
+    }
+
+    //charged weapons can be defined anywhere in the file, so they are looked up once everything is read
+    for i in 0..weapons.len() {
+        let mut ids = Vec::new();
+        for name in &weapons[i].charged {
+            match find_weapon(&weapons, name) {
+                Some(id) if weapons[id.0].kind == WeaponKind::Projectile => ids.push(id),
+                _ => {
+                    return Err(format!(
+                        "weapon \"{}\" charges into \"{}\", which is not a projectile weapon in the file",
+                        weapons[i].name, name
+                    ))
+                }
+            }
+        }
+        weapons[i].charged_ids = ids;
+    }
+    Ok(weapons)
+}
+
+//load the weapons file
+pub fn load_weapons(path: &Path, models: &[ModelData]) -> Result<Vec<WeaponDef>, String> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(t) => t,
+        Err(error) => return Err(format!("{} could not be read: {:?}", path.display(), error)),
+    };
+    match parse_weapons(&text, models) {
+        Ok(weapons) => Ok(weapons),
+        Err(error) => Err(format!("{}: {}", path.display(), error)),
+    }
+}
+
+//look up a weapon by the name it has in the data file
+pub fn find_weapon(weapons: &[WeaponDef], name: &str) -> Option<WeaponId> {
+    weapons.iter().position(|w| w.name == name).map(WeaponId)
+}
+
+//the weapon type for an entity holding the weapon with this name. unarmed if there is no such weapon
+pub fn equip_weapon(weapons: &[WeaponDef], name: &str) -> WeaponType {
+    match find_weapon(weapons, name) {
+        Some(id) => weapons[id.0].equip(id),
+        None => WeaponType::None,
+    }
+}